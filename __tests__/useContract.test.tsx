@@ -1,5 +1,9 @@
 import useContract from '@/lib/hooks/useContract';
-import { ScheduleConfiguration } from '@/lib/models/schedule-configuration.model';
+import {
+  MissedExecutionPolicy,
+  ScheduleConfiguration,
+  ScheduleStatus,
+} from '@/lib/models/schedule-configuration.model';
 import { BN } from '@polkadot/util';
 import { renderHook } from '@testing-library/react';
 import { RecoilRoot } from 'recoil';
@@ -15,12 +19,15 @@ const { queryContractMock, getExecuteContractExtrinsicMock } =
 const scheduleConfigurationMock: ScheduleConfiguration = {
   id: '0x',
   amount: '1',
-  enabled: true,
   recipient: '',
   sender: '',
   taskId: '',
   tokenAddress: '',
   executionTimes: [],
+  splits: [],
+  endConditions: {},
+  missedExecutionPolicy: MissedExecutionPolicy.Skip,
+  status: ScheduleStatus.Active,
 };
 
 describe('useContract', () => {
@@ -59,7 +66,6 @@ describe('useContract', () => {
     // Act
     const received = await result.current.getTriggerPaymentExtrinsic(
       scheduleConfigurationMock.sender,
-      scheduleConfigurationMock.recipient,
       new BN(scheduleConfigurationMock.amount),
       scheduleConfigurationMock.tokenAddress,
       scheduleConfigurationMock.id
@@ -1,14 +1,50 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+pub mod recurrence;
+
 #[ink::contract]
 mod tempora_contract {
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
-    use ink::storage::Mapping;
+    use ink::storage::{traits::StorageKey, Lazy, Mapping};
+    use scale::Encode;
+
+    use openbrush::contracts::traits::psp22::{extensions::metadata::PSP22MetadataRef, PSP22Ref};
+
+    use crate::recurrence::RecurrenceRule;
+
+    /// Seconds a payment can be triggered before or after its scheduled time.
+    pub const DEFAULT_EXECUTION_TOLERANCE: u64 = 900;
+
+    /// Maximum number of additional recipients a schedule can split its payments with.
+    pub const MAX_PAYMENT_SPLITS: usize = 20;
+
+    const BASIS_POINTS_DENOMINATOR: u16 = 10_000;
+
+    /// Maximum number of entries returned by a paginated query.
+    pub const MAX_PAGE_SIZE: u32 = 50;
+
+    /// Maximum number of slots a single trigger or claim settles.
+    pub const MAX_SLOTS_PER_SETTLEMENT: usize = 50;
 
-    use openbrush::contracts::traits::psp22::PSP22Ref;
+    /// Version of the storage layout written by this code. The first deployed layout, which
+    /// stored no version, is version 0.
+    ///
+    /// The root only holds the fields of that first layout, so any deployment can decode it.
+    /// Layout changes add new state in `Mapping`s or `Lazy` fields and a `migrate` step from
+    /// the previous version.
+    pub const STORAGE_VERSION: u32 = 3;
 
-    #[derive(Debug, scale::Decode, scale::Encode, PartialEq)]
+    /// Maximum number of schedules a single `trigger_many` or `save_schedules` call handles.
+    pub const MAX_SCHEDULES_PER_BATCH: usize = 50;
+
+    /// Seconds covered by each time bucket of the due schedules index.
+    pub const DUE_BUCKET_DURATION: u64 = 60 * 60;
+
+    /// Seconds a proposed admin has to accept the handover.
+    pub const ADMIN_PROPOSAL_DURATION: u64 = 7 * 24 * 60 * 60;
+
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
     #[cfg_attr(
         feature = "std",
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
@@ -27,6 +63,237 @@ mod tempora_contract {
         TokenIsAlreadyWhiteslited,
         TokenIsNotWhitelisted,
         Unauthorized,
+        SplitPaymentFailed,
+        InvalidScheduleStatusTransition,
+        EndConditionReached,
+        UpgradeFailed,
+        UnsupportedStorageVersion,
+        RoleAlreadyGranted,
+        RoleNotGranted,
+        CannotRevokeAdmin,
+        NoPendingAdmin,
+        AdminProposalExpired,
+        ContractPaused,
+        ContractNotPaused,
+        TokenIsFrozen,
+        TokenIsNotFrozen,
+        ScheduleHasPendingPayments,
+        InvalidTokenLimits,
+        AmountOutsideTokenLimits,
+        InvalidProtocolFee,
+        BatchTooLarge,
+        UnexpectedTransferredValue,
+    }
+
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, Copy, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Role {
+        /// Manages roles, the admin, the execution tolerance and upgrades
+        SuperAdmin,
+        WhitelistManager,
+        Pauser,
+        FeeManager,
+    }
+
+    impl Role {
+        pub const ALL: [Role; 4] = [
+            Role::SuperAdmin,
+            Role::WhitelistManager,
+            Role::Pauser,
+            Role::FeeManager,
+        ];
+    }
+
+    /// Lifecycle of a schedule. Only `Active` schedules can be paid.
+    ///
+    /// `Expired` is never stored: it is reported by the queries for `Active` schedules
+    /// that have no slot left to pay.
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, Copy, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum ScheduleStatus {
+        Active,
+        Paused,
+        Cancelled,
+        Completed,
+        Expired,
+    }
+
+    impl ScheduleStatus {
+        fn can_transition_to(&self, status: ScheduleStatus) -> bool {
+            matches!(
+                (self, status),
+                (ScheduleStatus::Active, ScheduleStatus::Paused)
+                    | (ScheduleStatus::Paused, ScheduleStatus::Active)
+                    | (ScheduleStatus::Active, ScheduleStatus::Completed)
+                    | (
+                        ScheduleStatus::Active | ScheduleStatus::Paused,
+                        ScheduleStatus::Cancelled
+                    )
+            )
+        }
+    }
+
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, Copy, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum ScheduleRole {
+        Sender,
+        Recipient,
+    }
+
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum SplitShare {
+        Amount(Balance),
+        BasisPoints(u16),
+    }
+
+    /// Part of every payment of a schedule that goes to an additional recipient.
+    ///
+    /// The schedule's own recipient receives whatever the splits leave of the amount,
+    /// including the rounding dust of basis point shares.
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PaymentSplit {
+        pub recipient: AccountId,
+        pub share: SplitShare,
+    }
+
+    impl PaymentSplit {
+        fn get_amount(&self, total_amount: Balance) -> Balance {
+            match self.share {
+                SplitShare::Amount(amount) => amount,
+                SplitShare::BasisPoints(basis_points) => {
+                    total_amount.saturating_mul(basis_points.into())
+                        / Balance::from(BASIS_POINTS_DENOMINATOR)
+                }
+            }
+        }
+    }
+
+    /// Fee the protocol deducts from every triggered payment: `basis_points` of the amount,
+    /// but at least the flat minimum of the payment's token, if it has one. `None` stands
+    /// for the native token.
+    #[derive(Debug, Default, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ProtocolFee {
+        pub basis_points: u16,
+        pub minimum_fees: Vec<(Option<AccountId>, Balance)>,
+    }
+
+    impl ProtocolFee {
+        /// Returns the fee of a single payment of `amount`, which never exceeds it.
+        pub fn get_amount(&self, token_address: Option<AccountId>, amount: Balance) -> Balance {
+            let proportional_fee =
+                amount.saturating_mul(self.basis_points.into()) / BASIS_POINTS_DENOMINATOR as u128;
+
+            let minimum_fee = self
+                .minimum_fees
+                .iter()
+                .find(|(token, _)| *token == token_address)
+                .map_or(0, |(_, minimum_fee)| *minimum_fee);
+
+            proportional_fee.max(minimum_fee).min(amount)
+        }
+    }
+
+    /// Registry entry of a whitelisted PSP22 token.
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct TokenInfo {
+        pub symbol: Option<String>,
+        pub decimals: u8,
+        /// Minimum amount of a single payment.
+        pub min_amount: Balance,
+        /// Maximum amount of a single payment, if any.
+        pub max_amount: Option<Balance>,
+        /// Whether schedules can be created and paid in the token.
+        pub enabled: bool,
+    }
+
+    impl Default for TokenInfo {
+        fn default() -> Self {
+            Self {
+                symbol: None,
+                decimals: 0,
+                min_amount: 0,
+                max_amount: None,
+                enabled: true,
+            }
+        }
+    }
+
+    /// Continuous payment that accrues `rate_per_second` from `start_time` until `stop_time`.
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct StreamConfiguration {
+        pub rate_per_second: Balance,
+        pub start_time: Timestamp,
+        pub stop_time: Option<Timestamp>,
+    }
+
+    /// Optional limits of a recurring or fixed schedule. It is completed as soon as any
+    /// of them is reached.
+    #[derive(Debug, Default, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EndConditions {
+        /// Slots after this timestamp are not paid
+        pub end_time: Option<Timestamp>,
+        pub max_executions: Option<u32>,
+        /// Total amount that can be paid, splits included
+        pub max_total_amount: Option<Balance>,
+    }
+
+    /// What happens to slots whose tolerance window elapsed without being paid.
+    #[derive(Debug, Default, scale::Decode, scale::Encode, Clone, Copy, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum MissedExecutionPolicy {
+        /// Missed slots are never paid and a trigger only pays the open slot
+        #[default]
+        Skip,
+        /// A trigger pays every missed slot along with the open one
+        CatchUp,
+        /// Missed slots accrue to their recipients, who claim them with `claim_accrued`
+        Accumulate,
+    }
+
+    #[derive(Debug, Default, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ScheduleProgress {
+        pub executions: u32,
+        pub amount_paid: Balance,
     }
 
     #[derive(scale::Decode, scale::Encode, Clone)]
@@ -35,6 +302,26 @@ mod tempora_contract {
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
     pub struct ScheduleConfiguration {
+        pub id: Hash,
+        pub task_id: String,
+        pub sender: AccountId,
+        pub recipient: AccountId,
+        pub amount: Balance,
+        pub token_address: Option<AccountId>,
+        pub start_time: Option<Timestamp>,
+        pub interval: Option<u64>,
+        pub execution_times: Option<Vec<Timestamp>>,
+        pub recurrence: Option<RecurrenceRule>,
+        pub stream: Option<StreamConfiguration>,
+        pub splits: Vec<PaymentSplit>,
+        pub end_conditions: EndConditions,
+        pub missed_execution_policy: MissedExecutionPolicy,
+        pub status: ScheduleStatus,
+    }
+
+    /// Schedule layout of the first deployed version, read by `migrate_legacy_schedules`.
+    #[derive(scale::Decode, scale::Encode)]
+    pub struct LegacyScheduleConfiguration {
         pub id: Hash,
         pub task_id: String,
         pub sender: AccountId,
@@ -47,6 +334,62 @@ mod tempora_contract {
         pub enabled: bool,
     }
 
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum PaymentOutcome {
+        Success,
+        Failed(TemporaError),
+    }
+
+    /// Record of a single payment attempt to one recipient.
+    ///
+    /// `slot_index` is `None` for payments that do not settle a slot, such as stream
+    /// withdrawals and claims of accrued amounts.
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PaymentReceipt {
+        pub receipt_id: u64,
+        pub schedule_id: Hash,
+        pub slot: Timestamp,
+        pub slot_index: Option<u32>,
+        pub timestamp: Timestamp,
+        pub block_number: BlockNumber,
+        pub executor: AccountId,
+        pub recipient: AccountId,
+        pub amount: Balance,
+        /// Protocol fee deducted from the payment, recorded with its first recipient.
+        pub fee: Balance,
+        pub token_address: Option<AccountId>,
+        pub outcome: PaymentOutcome,
+    }
+
+    /// A schedule to create through `save_schedules`, with the arguments of `save_schedule`.
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct NewSchedule {
+        pub id: Hash,
+        pub task_id: String,
+        pub recipient: AccountId,
+        pub amount: Balance,
+        pub token_address: Option<AccountId>,
+        pub start_time: Option<u64>,
+        pub interval: Option<u64>,
+        pub execution_times: Option<Vec<u64>>,
+        pub recurrence: Option<RecurrenceRule>,
+        pub splits: Vec<PaymentSplit>,
+        pub end_conditions: EndConditions,
+        pub missed_execution_policy: MissedExecutionPolicy,
+    }
+
     #[derive(scale::Decode, scale::Encode, Clone)]
     #[cfg_attr(
         feature = "std",
@@ -54,7 +397,268 @@ mod tempora_contract {
     )]
     pub struct UserScheduleData {
         pub schedule_configuration: ScheduleConfiguration,
-        pub payment_executions: Vec<Timestamp>,
+        /// The latest `MAX_PAGE_SIZE` receipts, oldest first.
+        pub payment_executions: Vec<PaymentReceipt>,
+        pub progress: ScheduleProgress,
+    }
+
+    #[ink(event)]
+    pub struct ScheduleCreated {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        #[ink(topic)]
+        pub recipient: AccountId,
+        pub token_address: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct ScheduleUpdated {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        #[ink(topic)]
+        pub recipient: AccountId,
+        pub token_address: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct ScheduleStatusChanged {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        #[ink(topic)]
+        pub recipient: AccountId,
+        pub previous_status: ScheduleStatus,
+        pub status: ScheduleStatus,
+    }
+
+    #[ink(event)]
+    pub struct PaymentExecuted {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        #[ink(topic)]
+        pub recipient: AccountId,
+        pub token_address: Option<AccountId>,
+        pub amount: Balance,
+        pub fee: Balance,
+        pub slot: Timestamp,
+        pub receipt_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct PaymentFailed {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        #[ink(topic)]
+        pub recipient: AccountId,
+        pub token_address: Option<AccountId>,
+        pub amount: Balance,
+        pub slot: Timestamp,
+        pub reason: TemporaError,
+        pub receipt_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct PaymentAccrued {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub recipient: AccountId,
+        pub amount: Balance,
+        pub slot: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct EscrowDeposited {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        pub token_address: Option<AccountId>,
+        pub amount: Balance,
+        pub balance: Balance,
+    }
+
+    #[ink(event)]
+    pub struct EscrowWithdrawn {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        pub token_address: Option<AccountId>,
+        pub amount: Balance,
+        pub balance: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CodeUpgraded {
+        pub code_hash: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct StorageMigrated {
+        pub from_version: u32,
+        pub to_version: u32,
+    }
+
+    #[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingAdmin {
+        pub account: AccountId,
+        pub expires_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        pub role: Role,
+        #[ink(topic)]
+        pub account: AccountId,
+        #[ink(topic)]
+        pub granted_by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        pub role: Role,
+        #[ink(topic)]
+        pub account: AccountId,
+        #[ink(topic)]
+        pub revoked_by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        pub account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AdminProposed {
+        #[ink(topic)]
+        pub current_admin: AccountId,
+        #[ink(topic)]
+        pub proposed_admin: AccountId,
+        pub expires_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct AdminProposalCancelled {
+        #[ink(topic)]
+        pub proposed_admin: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AdminChanged {
+        #[ink(topic)]
+        pub previous_admin: AccountId,
+        #[ink(topic)]
+        pub new_admin: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ExecutionToleranceChanged {
+        pub tolerance: u64,
+    }
+
+    #[ink(event)]
+    pub struct TokenWhitelisted {
+        #[ink(topic)]
+        pub token_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ScheduleTokenDelisted {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub sender: AccountId,
+        #[ink(topic)]
+        pub token_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TokenFrozen {
+        #[ink(topic)]
+        pub token_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TokenUnfrozen {
+        #[ink(topic)]
+        pub token_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct KeeperTipChanged {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        pub tip: Balance,
+    }
+
+    #[ink(event)]
+    pub struct KeeperTipPaid {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub keeper: AccountId,
+        pub token_address: Option<AccountId>,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ProtocolFeeChanged {
+        #[ink(topic)]
+        pub version: u32,
+        pub protocol_fee: ProtocolFee,
+    }
+
+    #[ink(event)]
+    pub struct ProtocolFeeAccepted {
+        #[ink(topic)]
+        pub schedule_id: Hash,
+        #[ink(topic)]
+        pub version: u32,
+    }
+
+    #[ink(event)]
+    pub struct FeesWithdrawn {
+        #[ink(topic)]
+        pub token_address: Option<AccountId>,
+        #[ink(topic)]
+        pub to: AccountId,
+        pub amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TokenInfoUpdated {
+        #[ink(topic)]
+        pub token_address: AccountId,
+        pub token_info: TokenInfo,
+    }
+
+    #[ink(event)]
+    pub struct TokenDelisted {
+        #[ink(topic)]
+        pub token_address: AccountId,
     }
 
     #[ink(storage)]
@@ -62,8 +666,53 @@ mod tempora_contract {
         pub admin: AccountId,
         pub schedules: Mapping<Hash, ScheduleConfiguration>,
         pub user_schedules: Mapping<AccountId, Vec<Hash>>,
-        pub payment_executions: Mapping<Hash, Vec<Timestamp>>,
+        /// Receipts of each schedule by their position in its history.
+        pub payment_executions: Mapping<(Hash, u32), PaymentReceipt>,
         pub tokens_whitelist: Vec<AccountId>,
+        pub execution_tolerance: Lazy<u64>,
+        pub last_settled_slots: Mapping<Hash, Timestamp>,
+        pub escrow_balances: Mapping<Hash, Balance>,
+        pub stream_withdrawals: Mapping<Hash, Balance>,
+        pub schedule_progress: Mapping<Hash, ScheduleProgress>,
+        pub settled_slots: Mapping<(Hash, u32), Timestamp>,
+        pub last_settled_slot_indices: Mapping<Hash, u32>,
+        pub accrued_amounts: Mapping<(Hash, AccountId), Balance>,
+        pub accrued_totals: Mapping<Hash, Balance>,
+        pub next_receipt_id: Lazy<u64>,
+        pub receipt_locations: Mapping<u64, (Hash, u32)>,
+        pub storage_version: Lazy<u32>,
+        pub role_members: Mapping<Role, Vec<AccountId>>,
+        pub pending_admin: Lazy<Option<PendingAdmin>>,
+        pub paused: Lazy<bool>,
+        pub frozen_tokens: Mapping<AccountId, ()>,
+        /// Ids of the active and paused schedules paying in each token, by position.
+        pub token_schedules: Mapping<(AccountId, u32), Hash>,
+        pub delisted_schedules: Mapping<Hash, ()>,
+        pub token_registry: Mapping<AccountId, TokenInfo>,
+        /// Every protocol fee set so far, by version. Version 0 is no fee.
+        pub protocol_fees: Mapping<u32, ProtocolFee>,
+        pub protocol_fee_version: Lazy<u32>,
+        pub schedule_fee_versions: Mapping<Hash, u32>,
+        pub collected_fees: Mapping<Option<AccountId>, Balance>,
+        pub keeper_tips: Mapping<Hash, Balance>,
+        /// Ids of the schedules with a keeper tip, by position.
+        pub tipped_schedules: Mapping<u32, Hash>,
+        /// Ids of the schedules whose next payable slot falls in each time bucket, by position.
+        pub due_schedules: Mapping<(u64, u32), Hash>,
+        /// Non-empty buckets of `due_schedules`, by position in no particular order.
+        pub due_buckets: Mapping<u32, u64>,
+        pub schedule_due_times: Mapping<Hash, Timestamp>,
+        pub payment_execution_counts: Mapping<Hash, u32>,
+        pub token_schedule_counts: Mapping<AccountId, u32>,
+        pub token_schedule_positions: Mapping<Hash, u32>,
+        pub tipped_schedule_count: Lazy<u32>,
+        pub tipped_schedule_positions: Mapping<Hash, u32>,
+        pub due_bucket_sizes: Mapping<u64, u32>,
+        pub schedule_due_positions: Mapping<Hash, u32>,
+        pub due_bucket_count: Lazy<u32>,
+        pub due_bucket_positions: Mapping<u64, u32>,
+        /// Protocol fees of accrued slots that have not been collected yet, by schedule.
+        pub accrued_fees: Mapping<Hash, Balance>,
     }
 
     impl TemporaContract {
@@ -71,652 +720,8513 @@ mod tempora_contract {
         pub fn new() -> Self {
             let caller = Self::env().caller();
 
-            Self {
+            let mut contract = Self {
                 admin: caller,
                 schedules: Mapping::default(),
                 user_schedules: Mapping::default(),
                 payment_executions: Mapping::default(),
                 tokens_whitelist: Vec::new(),
+                execution_tolerance: Lazy::default(),
+                last_settled_slots: Mapping::default(),
+                escrow_balances: Mapping::default(),
+                stream_withdrawals: Mapping::default(),
+                schedule_progress: Mapping::default(),
+                settled_slots: Mapping::default(),
+                last_settled_slot_indices: Mapping::default(),
+                accrued_amounts: Mapping::default(),
+                accrued_totals: Mapping::default(),
+                next_receipt_id: Lazy::default(),
+                receipt_locations: Mapping::default(),
+                storage_version: Lazy::default(),
+                role_members: Mapping::default(),
+                pending_admin: Lazy::default(),
+                paused: Lazy::default(),
+                frozen_tokens: Mapping::default(),
+                token_schedules: Mapping::default(),
+                delisted_schedules: Mapping::default(),
+                token_registry: Mapping::default(),
+                protocol_fees: Mapping::default(),
+                protocol_fee_version: Lazy::default(),
+                schedule_fee_versions: Mapping::default(),
+                collected_fees: Mapping::default(),
+                keeper_tips: Mapping::default(),
+                tipped_schedules: Mapping::default(),
+                due_schedules: Mapping::default(),
+                due_buckets: Mapping::default(),
+                schedule_due_times: Mapping::default(),
+                payment_execution_counts: Mapping::default(),
+                token_schedule_counts: Mapping::default(),
+                token_schedule_positions: Mapping::default(),
+                tipped_schedule_count: Lazy::default(),
+                tipped_schedule_positions: Mapping::default(),
+                due_bucket_sizes: Mapping::default(),
+                schedule_due_positions: Mapping::default(),
+                due_bucket_count: Lazy::default(),
+                due_bucket_positions: Mapping::default(),
+                accrued_fees: Mapping::default(),
+            };
+
+            contract
+                .execution_tolerance
+                .set(&DEFAULT_EXECUTION_TOLERANCE);
+            contract.storage_version.set(&STORAGE_VERSION);
+
+            for role in Role::ALL {
+                contract.add_role_member(role, caller);
             }
+
+            contract
         }
 
+        /// Proposes `new_admin` as the next admin, replacing any pending proposal. The
+        /// handover only happens once they accept it through `accept_admin`.
         #[ink(message)]
-        pub fn set_admin(&mut self, new_admin: AccountId) -> Result<(), TemporaError> {
-            self.ensure_admin()?;
+        pub fn propose_admin(&mut self, new_admin: AccountId) -> Result<(), TemporaError> {
+            self.ensure_role(Role::SuperAdmin)?;
 
-            self.admin = new_admin;
+            let expires_at = self
+                .current_timestamp_in_seconds()
+                .saturating_add(ADMIN_PROPOSAL_DURATION);
+
+            self.pending_admin.set(&Some(PendingAdmin {
+                account: new_admin,
+                expires_at,
+            }));
+
+            self.env().emit_event(AdminProposed {
+                current_admin: self.admin,
+                proposed_admin: new_admin,
+                expires_at,
+            });
 
             Ok(())
         }
 
+        /// Makes the caller the admin if they are the pending admin and the proposal has not
+        /// expired. They also take over the `SuperAdmin` role of the previous admin.
         #[ink(message)]
-        pub fn add_token_to_whitelist(
-            &mut self,
-            token_address: AccountId,
-        ) -> Result<(), TemporaError> {
-            self.ensure_admin()?;
+        pub fn accept_admin(&mut self) -> Result<(), TemporaError> {
+            let Some(pending_admin) = self.get_pending_admin() else {
+                return Err(TemporaError::NoPendingAdmin);
+            };
 
-            if self.token_is_whitelisted(token_address) {
-                return Err(TemporaError::TokenIsAlreadyWhiteslited);
+            let new_admin = self.env().caller();
+
+            if pending_admin.account != new_admin {
+                return Err(TemporaError::Unauthorized);
             }
 
-            self.tokens_whitelist.push(token_address);
+            if self.current_timestamp_in_seconds() > pending_admin.expires_at {
+                return Err(TemporaError::AdminProposalExpired);
+            }
 
-            Ok(())
-        }
+            let previous_admin = self.admin;
 
-        #[ink(message)]
-        pub fn remove_token_from_whitelist(
-            &mut self,
-            token_address: AccountId,
-        ) -> Result<(), TemporaError> {
-            self.ensure_admin()?;
+            self.admin = new_admin;
 
-            self.validate_token_is_whitelisted(token_address)?;
+            self.pending_admin.set(&None);
 
-            let index = self
-                .tokens_whitelist
-                .iter()
-                .position(|token| *token == token_address)
-                .unwrap();
+            self.remove_role_member(Role::SuperAdmin, previous_admin);
+            self.add_role_member(Role::SuperAdmin, new_admin);
 
-            self.tokens_whitelist.remove(index);
+            self.env().emit_event(AdminChanged {
+                previous_admin,
+                new_admin,
+            });
 
             Ok(())
         }
 
         #[ink(message)]
-        pub fn get_whitelisted_tokens(&self) -> Vec<AccountId> {
-            self.tokens_whitelist.clone()
-        }
+        pub fn cancel_admin_proposal(&mut self) -> Result<(), TemporaError> {
+            self.ensure_role(Role::SuperAdmin)?;
 
-        #[ink(message)]
-        pub fn save_schedule(
-            &mut self,
-            id: Hash,
-            task_id: String,
-            recipient: AccountId,
-            amount: Balance,
-            token_address: Option<AccountId>,
-            start_time: Option<u64>,
-            interval: Option<u64>,
-            execution_times: Option<Vec<u64>>,
-        ) -> Result<(), TemporaError> {
-            let caller = self.env().caller();
+            let Some(pending_admin) = self.get_pending_admin() else {
+                return Err(TemporaError::NoPendingAdmin);
+            };
 
-            self.validate_schedule(
-                &id,
-                caller,
-                recipient,
-                amount,
-                token_address,
-                start_time,
-                execution_times.clone(),
-                true,
-            )?;
+            self.pending_admin.set(&None);
 
-            let new_schedule = ScheduleConfiguration {
-                id: id,
-                task_id: String::from(&task_id),
-                sender: caller,
-                recipient,
-                amount,
-                token_address,
-                start_time,
-                interval,
-                execution_times,
-                enabled: true,
-            };
+            self.env().emit_event(AdminProposalCancelled {
+                proposed_admin: pending_admin.account,
+            });
+
+            Ok(())
+        }
 
-            self.schedules.insert(id, &new_schedule);
+        /// Returns the pending admin proposal, including an expired one until it is replaced
+        /// or cancelled.
+        #[ink(message)]
+        pub fn get_pending_admin(&self) -> Option<PendingAdmin> {
+            self.pending_admin.get().flatten()
+        }
+
+        /// Replaces the code of the contract, keeping its storage. The admin must call
+        /// `migrate` afterwards if the new code uses a newer storage layout.
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), TemporaError> {
+            self.ensure_role(Role::SuperAdmin)?;
 
-            self.update_user_schedules(caller, &id);
+            if ink::env::set_code_hash(&code_hash).is_err() {
+                return Err(TemporaError::UpgradeFailed);
+            }
 
-            self.update_user_schedules(recipient, &id);
+            self.env().emit_event(CodeUpgraded { code_hash });
 
             Ok(())
         }
 
+        /// Converts the storage left by older code to `STORAGE_VERSION`, one version at a
+        /// time. Does nothing if it is already up to date.
+        ///
+        /// Only the admin can migrate, as the roles may not have been migrated yet.
         #[ink(message)]
-        pub fn remove_schedule(&mut self, schedule_id: Hash) -> Result<(), TemporaError> {
-            let caller = self.env().caller();
+        pub fn migrate(&mut self) -> Result<(), TemporaError> {
+            self.ensure_admin()?;
 
-            self.validate_user_schedule_exists(caller, &schedule_id)?;
+            if self.get_storage_version() > STORAGE_VERSION {
+                return Err(TemporaError::UnsupportedStorageVersion);
+            }
 
-            let mut schedule = self.get_schedule_by_id(&schedule_id)?;
+            while self.get_storage_version() < STORAGE_VERSION {
+                let from_version = self.get_storage_version();
 
-            schedule.enabled = false;
+                self.migrate_from(from_version)?;
 
-            self.schedules.insert(schedule_id, &schedule);
+                self.storage_version.set(&(from_version + 1));
+
+                self.env().emit_event(StorageMigrated {
+                    from_version,
+                    to_version: from_version + 1,
+                });
+            }
 
             Ok(())
         }
 
+        /// Converts schedules saved by the first deployed version, along with their payment
+        /// history, to the current layout. Schedules already converted are left untouched.
+        ///
+        /// Their schedules cannot be read until converted, so the admin must call this with
+        /// every schedule id once `migrate` is done. At most `MAX_SCHEDULES_PER_BATCH` ids
+        /// are handled per call.
         #[ink(message)]
-        pub fn update_schedule(
+        pub fn migrate_legacy_schedules(
             &mut self,
-            schedule_configuration: ScheduleConfiguration,
+            schedule_ids: Vec<Hash>,
         ) -> Result<(), TemporaError> {
-            let caller = self.env().caller();
+            self.ensure_admin()?;
 
-            self.validate_user_schedule_exists(caller, &schedule_configuration.id)?;
+            if self.get_storage_version() != STORAGE_VERSION {
+                return Err(TemporaError::UnsupportedStorageVersion);
+            }
 
-            self.validate_schedule(
-                &schedule_configuration.id,
-                caller,
-                schedule_configuration.recipient,
-                schedule_configuration.amount,
-                schedule_configuration.token_address,
-                schedule_configuration.start_time,
-                schedule_configuration.execution_times.clone(),
-                false,
-            )?;
-
-            // If recipient could be edited, we should also modify user_schedules mapping
-            self.schedules
-                .insert(schedule_configuration.id, &schedule_configuration);
+            if schedule_ids.len() > MAX_SCHEDULES_PER_BATCH {
+                return Err(TemporaError::BatchTooLarge);
+            }
+
+            for schedule_id in schedule_ids {
+                self.migrate_legacy_schedule(schedule_id)?;
+            }
 
             Ok(())
         }
 
         #[ink(message)]
-        pub fn get_user_schedules(&self) -> Vec<UserScheduleData> {
-            let caller = self.env().caller();
-            let mut user_schedules = Vec::<UserScheduleData>::new();
-
-            match self.user_schedules.get(&caller) {
-                Some(schedule_ids) => {
-                    for schedule_id in schedule_ids {
-                        let schedule_configuration = self.schedules.get(&schedule_id).unwrap();
-                        let payment_executions = self
-                            .payment_executions
-                            .get(&schedule_id)
-                            .unwrap_or(Vec::new());
-
-                        user_schedules.push(UserScheduleData {
-                            schedule_configuration,
-                            payment_executions,
-                        });
-                    }
-                }
-                None => {}
-            }
-
-            user_schedules
+        pub fn get_storage_version(&self) -> u32 {
+            self.storage_version.get().unwrap_or(0)
         }
 
-        #[ink(message, payable)]
-        pub fn trigger_payment(
-            &mut self,
-            recipient: AccountId,
-            amount: Balance,
-            token_address: Option<AccountId>,
-            schedule_id: Hash,
-        ) -> Result<(), TemporaError> {
-            self.validate_trigger_payment(recipient, amount)?;
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<(), TemporaError> {
+            self.ensure_role(Role::SuperAdmin)?;
 
-            if token_address.is_some() {
-                self.trigger_psp22_payment(recipient, amount, token_address.unwrap())?;
-            } else {
-                self.trigger_native_payment(recipient, amount, self.env().transferred_value())?;
+            if !self.add_role_member(role, account) {
+                return Err(TemporaError::RoleAlreadyGranted);
             }
 
-            self.update_schedule_execution_time(&schedule_id);
+            self.env().emit_event(RoleGranted {
+                role,
+                account,
+                granted_by: self.env().caller(),
+            });
 
             Ok(())
         }
 
-        pub fn trigger_native_payment(
-            &mut self,
-            recipient: AccountId,
-            amount: Balance,
-            transferred_amount: Balance,
-        ) -> Result<(), TemporaError> {
-            if amount != transferred_amount {
-                return Err(TemporaError::InsufficientBalance);
-            }
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<(), TemporaError> {
+            self.ensure_role(Role::SuperAdmin)?;
 
-            if self.env().transfer(recipient, amount).is_err() {
-                return Err(TemporaError::TransferError);
-            }
+            self.remove_role(role, account)
+        }
 
-            // TODO: Emit Native transfer event
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<(), TemporaError> {
+            self.remove_role(role, self.env().caller())
+        }
 
-            Ok(())
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.get_role_members(role).contains(&account)
         }
 
-        pub fn trigger_psp22_payment(
-            &mut self,
-            recipient: AccountId,
-            amount: Balance,
-            token_address: AccountId,
-        ) -> Result<(), TemporaError> {
-            self.validate_token_is_whitelisted(token_address)?;
+        #[ink(message)]
+        pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+            self.role_members.get(role).unwrap_or_default()
+        }
 
-            let caller = self.env().caller();
+        /// Stops every payment and every schedule creation or update until `unpause` is
+        /// called. Schedules can still be removed and escrows withdrawn while paused.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), TemporaError> {
+            self.ensure_role(Role::Pauser)?;
+            self.ensure_not_paused()?;
 
-            if PSP22Ref::transfer_from(&token_address, caller, recipient, amount, Vec::new())
-                .is_err()
-            {
-                return Err(TemporaError::TransferError);
-            }
+            self.paused.set(&true);
 
-            // TODO: Emit PSP22 transfer event
+            self.env().emit_event(Paused {
+                account: self.env().caller(),
+            });
 
             Ok(())
         }
 
-        fn validate_schedule(
-            &self,
-            schedule_id: &Hash,
-            caller: AccountId,
-            recipient: AccountId,
-            amount: Balance,
-            token_address: Option<AccountId>,
-            start_time: Option<u64>,
-            execution_times: Option<Vec<u64>>,
-            is_new: bool,
-        ) -> Result<(), TemporaError> {
-            if is_new && self.schedules.contains(&schedule_id) {
-                return Err(TemporaError::ScheduleConfigurationAlreadyExists);
-            }
-
-            if caller == recipient {
-                return Err(TemporaError::CallerCannotBeRecipient);
-            }
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), TemporaError> {
+            self.ensure_role(Role::Pauser)?;
 
-            if amount == 0 {
-                return Err(TemporaError::ScheduleAmountCannotBeZero);
+            if !self.is_paused() {
+                return Err(TemporaError::ContractNotPaused);
             }
 
-            if token_address.is_some() {
-                self.validate_token_is_whitelisted(token_address.unwrap())?;
-            }
+            self.paused.set(&false);
 
-            if start_time.is_none() && execution_times.is_none() {
-                return Err(TemporaError::WrongScheduleConfiguration);
-            }
+            self.env().emit_event(Unpaused {
+                account: self.env().caller(),
+            });
 
             Ok(())
         }
 
-        fn validate_trigger_payment(
-            &self,
-            recipient: AccountId,
-            amount: Balance,
-        ) -> Result<(), TemporaError> {
-            let caller = self.env().caller();
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused.get().unwrap_or_default()
+        }
 
-            if recipient == caller {
-                return Err(TemporaError::CallerCannotBeRecipient);
-            }
+        #[ink(message)]
+        pub fn set_execution_tolerance(&mut self, tolerance: u64) -> Result<(), TemporaError> {
+            self.ensure_role(Role::SuperAdmin)?;
 
-            if amount == 0 {
-                return Err(TemporaError::ScheduleAmountCannotBeZero);
-            }
+            self.execution_tolerance.set(&tolerance);
+
+            self.env()
+                .emit_event(ExecutionToleranceChanged { tolerance });
 
             Ok(())
         }
 
-        fn token_is_whitelisted(&self, token_address: AccountId) -> bool {
-            self.tokens_whitelist.contains(&token_address)
+        #[ink(message)]
+        pub fn get_execution_tolerance(&self) -> u64 {
+            self.execution_tolerance
+                .get()
+                .unwrap_or(DEFAULT_EXECUTION_TOLERANCE)
         }
 
-        fn validate_token_is_whitelisted(
-            &self,
+        /// Registers the token, enabled and without payment limits, with the symbol and
+        /// decimals it provides through `PSP22Metadata`. These can be read again through
+        /// `refresh_token_metadata` or set through `set_token_info`.
+        #[ink(message)]
+        pub fn add_token_to_whitelist(
+            &mut self,
             token_address: AccountId,
         ) -> Result<(), TemporaError> {
-            if !self.token_is_whitelisted(token_address) {
-                return Err(TemporaError::TokenIsNotWhitelisted);
+            self.ensure_role(Role::WhitelistManager)?;
+
+            if self.token_registry.contains(token_address) {
+                return Err(TemporaError::TokenIsAlreadyWhiteslited);
             }
 
-            Ok(())
-        }
+            let token_info = self.read_token_metadata(token_address, TokenInfo::default());
 
-        fn validate_user_schedule_exists(
-            &self,
-            caller: AccountId,
-            schedule_id: &Hash,
-        ) -> Result<(), TemporaError> {
-            match self.schedules.get(&schedule_id) {
-                Some(schedule) => {
-                    if schedule.sender != caller {
-                        return Err(TemporaError::UserScheduleConfigurationNotFound);
-                    }
-                }
-                None => return Err(TemporaError::UserScheduleConfigurationNotFound),
-            }
+            self.tokens_whitelist.push(token_address);
+            self.token_registry.insert(token_address, &token_info);
+
+            self.env().emit_event(TokenWhitelisted { token_address });
 
             Ok(())
         }
 
-        fn get_schedule_by_id(
-            &self,
-            schedule_id: &Hash,
-        ) -> Result<ScheduleConfiguration, TemporaError> {
-            match self.schedules.get(&schedule_id) {
-                Some(schedule) => Ok(schedule),
-                None => Err(TemporaError::ScheduleConfigurationNotFound),
+        /// Removes the token from the whitelist and flags its active and paused schedules,
+        /// which can no longer be paid until their senders move them to another token through
+        /// `migrate_delisted_schedules`.
+        ///
+        /// At most `MAX_SCHEDULES_PER_BATCH` schedules are flagged here, the rest through
+        /// `flag_delisted_schedules`.
+        #[ink(message)]
+        pub fn remove_token_from_whitelist(
+            &mut self,
+            token_address: AccountId,
+        ) -> Result<(), TemporaError> {
+            self.ensure_role(Role::WhitelistManager)?;
+
+            if !self.token_registry.contains(token_address) {
+                return Err(TemporaError::TokenIsNotWhitelisted);
             }
-        }
 
-        fn update_schedule_execution_time(&mut self, schedule_id: &Hash) {
-            let current_timestamp_in_seconds =
-                self.env().block_timestamp().checked_div(1000).unwrap();
+            let index = self
+                .tokens_whitelist
+                .iter()
+                .position(|token| *token == token_address)
+                .unwrap();
 
-            let mut schedule_payment_executions = self
-                .payment_executions
-                .get(&schedule_id)
-                .unwrap_or(Vec::new());
+            self.tokens_whitelist.remove(index);
+            self.token_registry.remove(token_address);
 
-            schedule_payment_executions.push(current_timestamp_in_seconds);
+            self.flag_next_delisted_schedules(token_address);
 
-            self.payment_executions
-                .insert(schedule_id, &schedule_payment_executions);
-        }
+            self.env().emit_event(TokenDelisted { token_address });
 
-        fn update_user_schedules(&mut self, user_account_id: AccountId, schedule_id: &Hash) {
-            let mut user_schedules = self
-                .user_schedules
-                .get(&user_account_id)
-                .unwrap_or(Vec::new());
-            user_schedules.push(*schedule_id);
-            self.user_schedules.insert(user_account_id, &user_schedules);
+            Ok(())
         }
 
-        fn ensure_admin(&self) -> Result<(), TemporaError> {
-            if self.env().caller() != self.admin {
-                return Err(TemporaError::Unauthorized);
+        /// Flags the next `MAX_SCHEDULES_PER_BATCH` schedules of a delisted token left by
+        /// `remove_token_from_whitelist`, and returns how many are still to flag.
+        #[ink(message)]
+        pub fn flag_delisted_schedules(
+            &mut self,
+            token_address: AccountId,
+        ) -> Result<u32, TemporaError> {
+            self.ensure_role(Role::WhitelistManager)?;
+
+            if self.token_registry.contains(token_address) {
+                return Err(TemporaError::TokenIsAlreadyWhiteslited);
             }
 
-            Ok(())
+            Ok(self.flag_next_delisted_schedules(token_address))
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use ink::env::test::{
-            default_accounts, set_caller, set_value_transferred, DefaultAccounts,
-        };
-        use ink::env::DefaultEnvironment;
-        use ink::primitives::AccountId;
+        /// Sets the protocol fee of new schedules. Existing schedules keep the fee they were
+        /// created with until their sender accepts the new one through `accept_protocol_fee`.
+        #[ink(message)]
+        pub fn set_protocol_fee(&mut self, protocol_fee: ProtocolFee) -> Result<(), TemporaError> {
+            self.ensure_role(Role::FeeManager)?;
 
-        fn get_default_accounts() -> DefaultAccounts<DefaultEnvironment> {
-            default_accounts::<DefaultEnvironment>()
-        }
+            if protocol_fee.basis_points > BASIS_POINTS_DENOMINATOR {
+                return Err(TemporaError::InvalidProtocolFee);
+            }
 
-        fn set_sender(sender: AccountId) {
-            set_caller::<DefaultEnvironment>(sender);
+            let version = self.get_protocol_fee_version() + 1;
+
+            self.protocol_fees.insert(version, &protocol_fee);
+            self.protocol_fee_version.set(&version);
+
+            self.env().emit_event(ProtocolFeeChanged {
+                version,
+                protocol_fee,
+            });
+
+            Ok(())
         }
 
-        fn set_value_to_transfer(amount: u128) {
-            set_value_transferred::<DefaultEnvironment>(amount);
+        #[ink(message)]
+        pub fn get_protocol_fee(&self) -> ProtocolFee {
+            self.protocol_fees
+                .get(self.get_protocol_fee_version())
+                .unwrap_or_default()
         }
 
-        fn init() -> (TemporaContract, DefaultAccounts<DefaultEnvironment>) {
-            (TemporaContract::new(), get_default_accounts())
+        #[ink(message)]
+        pub fn get_protocol_fee_version(&self) -> u32 {
+            self.protocol_fee_version.get().unwrap_or_default()
         }
 
-        #[ink::test]
-        fn set_admin_works() {
-            let (mut contract, accounts) = init();
+        /// Moves the schedule to the current protocol fee.
+        #[ink(message)]
+        pub fn accept_protocol_fee(&mut self, schedule_id: Hash) -> Result<(), TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
 
-            let _ = contract.set_admin(accounts.bob);
+            if schedule.sender != self.env().caller() {
+                return Err(TemporaError::Unauthorized);
+            }
 
-            assert_eq!(contract.admin, accounts.bob);
-        }
+            let version = self.get_protocol_fee_version();
 
-        #[ink::test]
-        fn set_admin_fails() {
-            let (mut contract, accounts) = init();
+            self.validate_splits(&schedule, self.get_fee_at_version(&schedule, version))?;
 
-            set_sender(accounts.bob);
-            let result = contract.set_admin(accounts.bob);
+            self.schedule_fee_versions.insert(schedule_id, &version);
 
-            assert_eq!(result, Err(TemporaError::Unauthorized));
+            self.env().emit_event(ProtocolFeeAccepted {
+                schedule_id,
+                version,
+            });
+
+            Ok(())
+        }
+
+        /// Returns the protocol fee deducted from each payment of the schedule.
+        #[ink(message)]
+        pub fn get_schedule_fee(&self, schedule_id: Hash) -> Result<Balance, TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            Ok(self.get_payment_fee(&schedule))
+        }
+
+        #[ink(message)]
+        pub fn get_collected_fees(&self, token_address: Option<AccountId>) -> Balance {
+            self.collected_fees.get(token_address).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn withdraw_fees(
+            &mut self,
+            token_address: Option<AccountId>,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), TemporaError> {
+            self.ensure_role(Role::FeeManager)?;
+
+            let collected_fees = self.get_collected_fees(token_address);
+
+            if amount > collected_fees {
+                return Err(TemporaError::InsufficientBalance);
+            }
+
+            self.transfer_from_contract(token_address, to, amount)?;
+
+            self.collected_fees
+                .insert(token_address, &(collected_fees - amount));
+
+            self.env().emit_event(FeesWithdrawn {
+                token_address,
+                to,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_whitelisted_tokens(&self) -> Vec<AccountId> {
+            self.tokens_whitelist.clone()
+        }
+
+        #[ink(message)]
+        pub fn set_token_info(
+            &mut self,
+            token_address: AccountId,
+            token_info: TokenInfo,
+        ) -> Result<(), TemporaError> {
+            self.ensure_role(Role::WhitelistManager)?;
+
+            if !self.token_registry.contains(token_address) {
+                return Err(TemporaError::TokenIsNotWhitelisted);
+            }
+
+            if token_info
+                .max_amount
+                .is_some_and(|max_amount| max_amount < token_info.min_amount)
+            {
+                return Err(TemporaError::InvalidTokenLimits);
+            }
+
+            self.token_registry.insert(token_address, &token_info);
+
+            self.env().emit_event(TokenInfoUpdated {
+                token_address,
+                token_info,
+            });
+
+            Ok(())
+        }
+
+        /// Reads the symbol and decimals of the token from its `PSP22Metadata`, keeping the
+        /// registered values of those the token does not provide.
+        #[ink(message)]
+        pub fn refresh_token_metadata(
+            &mut self,
+            token_address: AccountId,
+        ) -> Result<TokenInfo, TemporaError> {
+            self.ensure_role(Role::WhitelistManager)?;
+
+            let Some(token_info) = self.token_registry.get(token_address) else {
+                return Err(TemporaError::TokenIsNotWhitelisted);
+            };
+
+            let token_info = self.read_token_metadata(token_address, token_info);
+
+            self.token_registry.insert(token_address, &token_info);
+
+            self.env().emit_event(TokenInfoUpdated {
+                token_address,
+                token_info: token_info.clone(),
+            });
+
+            Ok(token_info)
+        }
+
+        #[ink(message)]
+        pub fn get_token_info(&self, token_address: AccountId) -> Option<TokenInfo> {
+            self.token_registry.get(token_address)
+        }
+
+        /// Returns a page of the registered tokens, in registration order. At most
+        /// `MAX_PAGE_SIZE` entries are returned.
+        #[ink(message)]
+        pub fn get_tokens(&self, offset: u32, limit: u32) -> Vec<(AccountId, TokenInfo)> {
+            self.tokens_whitelist
+                .iter()
+                .skip(offset as usize)
+                .take(limit.min(MAX_PAGE_SIZE) as usize)
+                .filter_map(|token_address| {
+                    self.token_registry
+                        .get(token_address)
+                        .map(|token_info| (*token_address, token_info))
+                })
+                .collect()
+        }
+
+        /// Moves every schedule of the caller flagged by the delisting of `token_address` to
+        /// `new_token_address`, and returns their ids.
+        ///
+        /// The available escrow of each schedule is refunded in the delisted token first. A
+        /// schedule still owing funds in the delisted token cannot be moved.
+        #[ink(message)]
+        pub fn migrate_delisted_schedules(
+            &mut self,
+            token_address: AccountId,
+            new_token_address: AccountId,
+        ) -> Result<Vec<Hash>, TemporaError> {
+            let caller = self.env().caller();
+
+            let schedules: Vec<ScheduleConfiguration> = self
+                .user_schedules
+                .get(caller)
+                .unwrap_or_default()
+                .iter()
+                .filter(|schedule_id| self.is_schedule_delisted(**schedule_id))
+                .filter_map(|schedule_id| self.schedules.get(schedule_id))
+                .filter(|schedule| {
+                    schedule.sender == caller && schedule.token_address == Some(token_address)
+                })
+                .collect();
+
+            for schedule in schedules.iter() {
+                let escrow_balance = self.get_available_escrow_balance(schedule);
+
+                if escrow_balance > 0 {
+                    self.withdraw_from_escrow(schedule, escrow_balance)?;
+                }
+
+                if self.get_escrow_balance(schedule.id) > 0 {
+                    return Err(TemporaError::ScheduleHasPendingPayments);
+                }
+
+                let migrated_schedule = ScheduleConfiguration {
+                    token_address: Some(new_token_address),
+                    ..schedule.clone()
+                };
+
+                self.validate_schedule(&migrated_schedule, false)?;
+
+                self.schedules
+                    .insert(migrated_schedule.id, &migrated_schedule);
+
+                self.delisted_schedules.remove(migrated_schedule.id);
+
+                self.remove_token_schedule(schedule);
+                self.update_token_schedules(&migrated_schedule);
+
+                self.env().emit_event(ScheduleUpdated {
+                    schedule_id: migrated_schedule.id,
+                    sender: migrated_schedule.sender,
+                    recipient: migrated_schedule.recipient,
+                    token_address: migrated_schedule.token_address,
+                });
+            }
+
+            Ok(schedules.iter().map(|schedule| schedule.id).collect())
+        }
+
+        /// Returns whether the schedule was flagged by the delisting of its token and has not
+        /// been migrated yet.
+        #[ink(message)]
+        pub fn is_schedule_delisted(&self, schedule_id: Hash) -> bool {
+            self.delisted_schedules.contains(schedule_id)
+        }
+
+        /// Blocks new schedules and every payment in the token until it is unfrozen. The
+        /// affected schedules are listed by `get_token_schedules`.
+        #[ink(message)]
+        pub fn freeze_token(&mut self, token_address: AccountId) -> Result<(), TemporaError> {
+            self.ensure_role(Role::WhitelistManager)?;
+
+            if self.is_token_frozen(token_address) {
+                return Err(TemporaError::TokenIsFrozen);
+            }
+
+            self.frozen_tokens.insert(token_address, &());
+
+            self.env().emit_event(TokenFrozen { token_address });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unfreeze_token(&mut self, token_address: AccountId) -> Result<(), TemporaError> {
+            self.ensure_role(Role::WhitelistManager)?;
+
+            if !self.is_token_frozen(token_address) {
+                return Err(TemporaError::TokenIsNotFrozen);
+            }
+
+            self.frozen_tokens.remove(token_address);
+
+            self.env().emit_event(TokenUnfrozen { token_address });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_token_frozen(&self, token_address: AccountId) -> bool {
+            self.frozen_tokens.contains(token_address)
+        }
+
+        /// Returns a page of the ids of the active and paused schedules paying in the token.
+        /// At most `MAX_PAGE_SIZE` entries are returned.
+        ///
+        /// A schedule leaving the list is replaced by the last one, so pages can shift while
+        /// schedules are cancelled or completed.
+        #[ink(message)]
+        pub fn get_token_schedules(
+            &self,
+            token_address: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<Hash> {
+            let count = self.token_schedule_counts.get(token_address).unwrap_or(0);
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+
+            (offset..end)
+                .filter_map(|position| self.token_schedules.get((token_address, position)))
+                .collect()
+        }
+
+        // Takes the fields of `NewSchedule` one by one, which is the interface clients use
+        #[allow(clippy::too_many_arguments)]
+        #[ink(message)]
+        pub fn save_schedule(
+            &mut self,
+            id: Hash,
+            task_id: String,
+            recipient: AccountId,
+            amount: Balance,
+            token_address: Option<AccountId>,
+            start_time: Option<u64>,
+            interval: Option<u64>,
+            execution_times: Option<Vec<u64>>,
+            recurrence: Option<RecurrenceRule>,
+            splits: Vec<PaymentSplit>,
+            end_conditions: EndConditions,
+            missed_execution_policy: MissedExecutionPolicy,
+        ) -> Result<(), TemporaError> {
+            self.ensure_not_paused()?;
+
+            let new_schedule = self.get_new_schedule_configuration(NewSchedule {
+                id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                recurrence,
+                splits,
+                end_conditions,
+                missed_execution_policy,
+            });
+
+            self.create_schedule(new_schedule)
+        }
+
+        /// Creates every schedule or, if any of them is invalid, none. Returns their ids.
+        ///
+        /// Every schedule is validated, including against the others in the batch, before any
+        /// of them is stored.
+        #[ink(message)]
+        pub fn save_schedules(
+            &mut self,
+            new_schedules: Vec<NewSchedule>,
+        ) -> Result<Vec<Hash>, TemporaError> {
+            self.ensure_not_paused()?;
+
+            if new_schedules.len() > MAX_SCHEDULES_PER_BATCH {
+                return Err(TemporaError::BatchTooLarge);
+            }
+
+            let new_schedules: Vec<ScheduleConfiguration> = new_schedules
+                .into_iter()
+                .map(|new_schedule| self.get_new_schedule_configuration(new_schedule))
+                .collect();
+
+            let mut schedule_ids: Vec<Hash> = Vec::new();
+
+            for new_schedule in new_schedules.iter() {
+                if schedule_ids.contains(&new_schedule.id) {
+                    return Err(TemporaError::ScheduleConfigurationAlreadyExists);
+                }
+
+                self.validate_schedule(new_schedule, true)?;
+
+                schedule_ids.push(new_schedule.id);
+            }
+
+            for new_schedule in new_schedules {
+                self.create_schedule(new_schedule)?;
+            }
+
+            Ok(schedule_ids)
+        }
+
+        /// Creates a stream schedule. Its `amount` holds the rate per second and it is funded
+        /// through `deposit`.
+        #[ink(message)]
+        pub fn save_stream(
+            &mut self,
+            id: Hash,
+            recipient: AccountId,
+            token_address: Option<AccountId>,
+            rate_per_second: Balance,
+            start_time: Timestamp,
+            stop_time: Option<Timestamp>,
+        ) -> Result<(), TemporaError> {
+            self.ensure_not_paused()?;
+
+            let new_schedule = ScheduleConfiguration {
+                id,
+                task_id: String::new(),
+                sender: self.env().caller(),
+                recipient,
+                amount: rate_per_second,
+                token_address,
+                start_time: None,
+                interval: None,
+                execution_times: None,
+                recurrence: None,
+                stream: Some(StreamConfiguration {
+                    rate_per_second,
+                    start_time,
+                    stop_time,
+                }),
+                splits: Vec::new(),
+                end_conditions: EndConditions::default(),
+                missed_execution_policy: MissedExecutionPolicy::default(),
+                status: ScheduleStatus::Active,
+            };
+
+            self.create_schedule(new_schedule)
+        }
+
+        #[ink(message)]
+        pub fn remove_schedule(&mut self, schedule_id: Hash) -> Result<(), TemporaError> {
+            let caller = self.env().caller();
+
+            self.validate_user_schedule_exists(caller, &schedule_id)?;
+
+            let mut schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            if schedule.status == ScheduleStatus::Active && schedule.stream.is_some() {
+                self.settle_stream(&schedule)?;
+
+                let now = self.current_timestamp_in_seconds();
+
+                if let Some(stream) = schedule.stream.as_mut() {
+                    stream.stop_time = Some(stream.stop_time.map_or(now, |stop| stop.min(now)));
+                }
+            }
+
+            self.update_schedule_status(&mut schedule, ScheduleStatus::Cancelled)?;
+
+            // Accrued amounts stay in escrow for their recipients to claim
+            let escrow_balance = self.get_available_escrow_balance(&schedule);
+
+            if escrow_balance > 0 {
+                self.withdraw_from_escrow(&schedule, escrow_balance)?;
+            }
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn pause_schedule(&mut self, schedule_id: Hash) -> Result<(), TemporaError> {
+            let caller = self.env().caller();
+
+            self.validate_user_schedule_exists(caller, &schedule_id)?;
+
+            let mut schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            // A stream keeps accruing with time, so it can only be cancelled
+            if schedule.stream.is_some() {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            self.update_schedule_status(&mut schedule, ScheduleStatus::Paused)
+        }
+
+        #[ink(message)]
+        pub fn resume_schedule(&mut self, schedule_id: Hash) -> Result<(), TemporaError> {
+            let caller = self.env().caller();
+
+            self.validate_user_schedule_exists(caller, &schedule_id)?;
+
+            let mut schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            self.update_schedule_status(&mut schedule, ScheduleStatus::Active)
+        }
+
+        #[ink(message)]
+        pub fn update_schedule(
+            &mut self,
+            schedule_configuration: ScheduleConfiguration,
+        ) -> Result<(), TemporaError> {
+            self.ensure_not_paused()?;
+
+            let caller = self.env().caller();
+
+            self.validate_user_schedule_exists(caller, &schedule_configuration.id)?;
+
+            if schedule_configuration.sender != caller {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            let stored_schedule = self.get_schedule_by_id(&schedule_configuration.id)?;
+
+            if !matches!(
+                stored_schedule.status,
+                ScheduleStatus::Active | ScheduleStatus::Paused
+            ) {
+                return Err(TemporaError::ScheduleConfigurationDisabled);
+            }
+
+            // The status only changes through the lifecycle messages
+            let schedule_configuration = ScheduleConfiguration {
+                status: stored_schedule.status,
+                ..schedule_configuration
+            };
+
+            if schedule_configuration.token_address != stored_schedule.token_address
+                && self.get_escrow_balance(schedule_configuration.id) > 0
+            {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            // Changing a stream would retroactively change what has already accrued
+            if schedule_configuration.stream != stored_schedule.stream {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            self.validate_schedule(&schedule_configuration, false)?;
+
+            self.schedules
+                .insert(schedule_configuration.id, &schedule_configuration);
+
+            self.update_recipients_schedules(&schedule_configuration);
+
+            self.index_due_schedule(&schedule_configuration.id);
+
+            if schedule_configuration.token_address != stored_schedule.token_address {
+                self.delisted_schedules.remove(schedule_configuration.id);
+
+                self.remove_token_schedule(&stored_schedule);
+                self.update_token_schedules(&schedule_configuration);
+            }
+
+            self.env().emit_event(ScheduleUpdated {
+                schedule_id: schedule_configuration.id,
+                sender: schedule_configuration.sender,
+                recipient: schedule_configuration.recipient,
+                token_address: schedule_configuration.token_address,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_user_schedules(&self) -> Vec<UserScheduleData> {
+            let caller = self.env().caller();
+
+            self.user_schedules
+                .get(caller)
+                .unwrap_or_default()
+                .iter()
+                .map(|schedule_id| self.get_user_schedule_data(schedule_id))
+                .collect()
+        }
+
+        /// Returns a page of the schedules of any account, optionally only those where it
+        /// has the given role. At most `MAX_PAGE_SIZE` entries are returned.
+        #[ink(message)]
+        pub fn get_account_schedules(
+            &self,
+            account: AccountId,
+            role: Option<ScheduleRole>,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<UserScheduleData> {
+            self.user_schedules
+                .get(account)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|schedule_id| self.schedules.get(schedule_id))
+                .filter(|schedule| match role {
+                    Some(ScheduleRole::Sender) => schedule.sender == account,
+                    Some(ScheduleRole::Recipient) => schedule.sender != account,
+                    None => true,
+                })
+                .skip(offset as usize)
+                .take(limit.min(MAX_PAGE_SIZE) as usize)
+                .map(|schedule| self.get_user_schedule_data(&schedule.id))
+                .collect()
+        }
+
+        /// Returns a page of the payment execution history of a schedule, oldest first.
+        /// At most `MAX_PAGE_SIZE` entries are returned.
+        #[ink(message)]
+        pub fn get_payment_executions(
+            &self,
+            schedule_id: Hash,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<PaymentReceipt> {
+            let count = self.payment_execution_counts.get(schedule_id).unwrap_or(0);
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+
+            (offset..end)
+                .filter_map(|position| self.payment_executions.get((schedule_id, position)))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn get_payment_receipt(&self, receipt_id: u64) -> Option<PaymentReceipt> {
+            let (schedule_id, position) = self.receipt_locations.get(receipt_id)?;
+
+            self.payment_executions.get((schedule_id, position))
+        }
+
+        /// Returns the next slots of a schedule that can still be paid, taking its end
+        /// conditions into account. At most `MAX_PAGE_SIZE` entries are returned.
+        #[ink(message)]
+        pub fn get_next_occurrences(
+            &self,
+            schedule_id: Hash,
+            count: u32,
+        ) -> Result<Vec<Timestamp>, TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            if schedule.stream.is_some() {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            if !matches!(
+                schedule.status,
+                ScheduleStatus::Active | ScheduleStatus::Paused
+            ) {
+                return Ok(Vec::new());
+            }
+
+            let progress = self.schedule_progress.get(schedule_id).unwrap_or_default();
+            let end_conditions = &schedule.end_conditions;
+
+            let remaining_executions = end_conditions
+                .max_executions
+                .map_or(u32::MAX, |max| max.saturating_sub(progress.executions));
+            let remaining_payments = end_conditions.max_total_amount.map_or(u128::MAX, |max| {
+                max.saturating_sub(progress.amount_paid) / schedule.amount
+            });
+
+            let limit = count
+                .min(MAX_PAGE_SIZE)
+                .min(remaining_executions)
+                .min(remaining_payments.try_into().unwrap_or(u32::MAX));
+
+            let from =
+                self.get_earliest_payable_slot(&schedule, self.current_timestamp_in_seconds());
+
+            Ok(self
+                .get_slots_from(&schedule, from, limit as usize)
+                .into_iter()
+                .filter(|slot| self.is_before_end_time(&schedule, *slot))
+                .collect())
+        }
+
+        /// Adds funds to the escrow of a schedule, in the schedule's token.
+        ///
+        /// Native deposits must transfer exactly `amount`; PSP22 deposits are pulled from the
+        /// caller's allowance.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self, schedule_id: Hash, amount: Balance) -> Result<(), TemporaError> {
+            let caller = self.env().caller();
+
+            self.validate_user_schedule_exists(caller, &schedule_id)?;
+
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            if !matches!(
+                schedule.status,
+                ScheduleStatus::Active | ScheduleStatus::Paused
+            ) {
+                return Err(TemporaError::ScheduleConfigurationDisabled);
+            }
+
+            if amount == 0 {
+                return Err(TemporaError::ScheduleAmountCannotBeZero);
+            }
+
+            if let Some(token_address) = schedule.token_address {
+                self.validate_token_is_whitelisted(token_address)?;
+
+                if PSP22Ref::transfer_from(
+                    &token_address,
+                    caller,
+                    self.env().account_id(),
+                    amount,
+                    Vec::new(),
+                )
+                .is_err()
+                {
+                    return Err(TemporaError::TransferError);
+                }
+            } else if self.env().transferred_value() != amount {
+                return Err(TemporaError::InsufficientBalance);
+            }
+
+            let balance = self.get_escrow_balance(schedule_id) + amount;
+
+            self.escrow_balances.insert(schedule_id, &balance);
+
+            self.env().emit_event(EscrowDeposited {
+                schedule_id,
+                sender: caller,
+                token_address: schedule.token_address,
+                amount,
+                balance,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn withdraw(&mut self, schedule_id: Hash, amount: Balance) -> Result<(), TemporaError> {
+            let caller = self.env().caller();
+
+            self.validate_user_schedule_exists(caller, &schedule_id)?;
+
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            // Funds already accrued to recipients are not the sender's to withdraw
+            let stream_owed_amount = match schedule.status {
+                ScheduleStatus::Active => self.get_stream_owed_amount(&schedule),
+                _ => 0,
+            };
+
+            if amount
+                > self
+                    .get_available_escrow_balance(&schedule)
+                    .saturating_sub(stream_owed_amount)
+            {
+                return Err(TemporaError::InsufficientBalance);
+            }
+
+            self.withdraw_from_escrow(&schedule, amount)
+        }
+
+        #[ink(message)]
+        pub fn get_escrow_balance(&self, schedule_id: Hash) -> Balance {
+            self.escrow_balances.get(schedule_id).unwrap_or(0)
+        }
+
+        /// Returns how many executions of the schedule its escrow balance still covers.
+        #[ink(message)]
+        pub fn get_escrow_covered_executions(
+            &self,
+            schedule_id: Hash,
+        ) -> Result<u128, TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            Ok(self.get_available_escrow_balance(&schedule) / schedule.amount)
+        }
+
+        /// Pays the recipient of a stream everything accrued so far, as far as its escrow covers.
+        #[ink(message)]
+        pub fn withdraw_stream(&mut self, schedule_id: Hash) -> Result<Balance, TemporaError> {
+            self.ensure_not_paused()?;
+
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            if schedule.recipient != self.env().caller() {
+                return Err(TemporaError::Unauthorized);
+            }
+
+            if schedule.stream.is_none() {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            if schedule.status != ScheduleStatus::Active {
+                return Err(TemporaError::ScheduleConfigurationDisabled);
+            }
+
+            self.validate_token_is_not_frozen(&schedule)?;
+
+            self.settle_stream(&schedule)
+        }
+
+        /// Returns the amount the recipient of a stream can withdraw right now.
+        #[ink(message)]
+        pub fn get_stream_withdrawable_amount(
+            &self,
+            schedule_id: Hash,
+        ) -> Result<Balance, TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            if schedule.stream.is_none() {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            Ok(self
+                .get_stream_owed_amount(&schedule)
+                .min(self.get_escrow_balance(schedule_id)))
+        }
+
+        /// Pays the due slots of a schedule with its stored recipient, amount and token.
+        ///
+        /// Which slots are due depends on the schedule's `MissedExecutionPolicy`. At most
+        /// `MAX_SLOTS_PER_SETTLEMENT` slots are settled per call, oldest first, so a longer
+        /// backlog takes several calls.
+        ///
+        /// The payment is drawn from the schedule's escrow when no value is transferred and the
+        /// escrow covers it; otherwise from the transferred value or the sender's allowance.
+        ///
+        /// Anyone can trigger a payment funded by the escrow or the sender's allowance, so
+        /// keepers can execute schedules on the sender's behalf. Only the sender can fund it
+        /// with transferred value, and only when native slots are due.
+        ///
+        /// A failed transfer triggered by the sender does not revert the call: any transferred
+        /// value is refunded, `PaymentFailed` is emitted and the slots stay open so they can be
+        /// retried. Triggered by another account, it fails with `TransferError` instead.
+        #[ink(message, payable)]
+        pub fn trigger_payment(&mut self, schedule_id: Hash) -> Result<(), TemporaError> {
+            self.ensure_not_paused()?;
+
+            let transferred_value = self.env().transferred_value();
+
+            if let PaymentOutcome::Failed(_) =
+                self.trigger_schedule(schedule_id, transferred_value)?
+            {
+                if transferred_value > 0
+                    && self
+                        .env()
+                        .transfer(self.env().caller(), transferred_value)
+                        .is_err()
+                {
+                    return Err(TemporaError::TransferError);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Triggers each schedule as `trigger_payment` does, and returns the outcome of each,
+        /// so a schedule that cannot be paid does not stop the others. A transfer that failed
+        /// for the schedule's sender is recorded and returned as `PaymentOutcome::Failed`.
+        ///
+        /// Native payments that their escrow does not cover are funded from the transferred
+        /// value, in order, and whatever is left of it is refunded. A split recipient that
+        /// cannot be paid does not revert the call either: its share is accrued for it to claim.
+        #[ink(message, payable)]
+        pub fn trigger_many(
+            &mut self,
+            schedule_ids: Vec<Hash>,
+        ) -> Result<Vec<Result<PaymentOutcome, TemporaError>>, TemporaError> {
+            self.ensure_not_paused()?;
+
+            if schedule_ids.len() > MAX_SCHEDULES_PER_BATCH {
+                return Err(TemporaError::BatchTooLarge);
+            }
+
+            let mut remaining_value = self.env().transferred_value();
+            let mut results = Vec::new();
+
+            for schedule_id in schedule_ids {
+                let value = self.get_batch_trigger_value(&schedule_id, remaining_value);
+
+                let result = self.trigger_schedule(schedule_id, value);
+
+                // The value of a payment that failed is refunded with the rest
+                if let Ok(PaymentOutcome::Success) = result {
+                    remaining_value -= value;
+                }
+
+                results.push(result);
+            }
+
+            if remaining_value > 0
+                && self
+                    .env()
+                    .transfer(self.env().caller(), remaining_value)
+                    .is_err()
+            {
+                return Err(TemporaError::TransferError);
+            }
+
+            Ok(results)
+        }
+
+        /// Returns what a trigger of the schedule would pay right now, which is more than its
+        /// amount when missed slots are caught up.
+        #[ink(message)]
+        pub fn get_due_amount(&self, schedule_id: Hash) -> Result<Balance, TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            let now = self.current_timestamp_in_seconds();
+            let earliest_open_slot = now.saturating_sub(self.get_execution_tolerance());
+
+            let due_slots = self
+                .get_slots_to_settle(&schedule, now)?
+                .into_iter()
+                .filter(|slot| {
+                    schedule.missed_execution_policy != MissedExecutionPolicy::Accumulate
+                        || *slot >= earliest_open_slot
+                })
+                .count();
+
+            Ok(schedule.amount.saturating_mul(due_slots as u128))
+        }
+
+        /// Returns up to `limit` ids of the active schedules whose next payable slot is not after
+        /// `until`, earliest first. At most `MAX_PAGE_SIZE` ids are returned.
+        ///
+        /// A slot can be triggered from `execution_tolerance` seconds before it. Schedules are
+        /// indexed when they are saved, updated, triggered or change status. As `Skip` schedules
+        /// can miss slots in between, the indexed time is only a lower bound and the next payable
+        /// slot is worked out again here.
+        #[ink(message)]
+        pub fn get_due_schedules(&self, until: Timestamp, limit: u32) -> Vec<Hash> {
+            let limit = limit.min(MAX_PAGE_SIZE) as usize;
+            let until_bucket = until / DUE_BUCKET_DURATION;
+
+            let mut buckets: Vec<u64> = (0..self.due_bucket_count.get().unwrap_or(0))
+                .filter_map(|position| self.due_buckets.get(position))
+                .filter(|bucket| *bucket <= until_bucket)
+                .collect();
+            buckets.sort_unstable();
+
+            let mut due_schedules: Vec<(Timestamp, Hash)> = Vec::new();
+
+            for bucket in buckets {
+                // Schedules never become payable before their indexed time, so later buckets
+                // cannot hold anything earlier than a full page.
+                if due_schedules.len() >= limit
+                    && due_schedules[limit - 1].0 < bucket.saturating_mul(DUE_BUCKET_DURATION)
+                {
+                    break;
+                }
+
+                for position in 0..self.due_bucket_sizes.get(bucket).unwrap_or(0) {
+                    let Some(schedule_id) = self.due_schedules.get((bucket, position)) else {
+                        continue;
+                    };
+
+                    if let Some(due_time) = self
+                        .schedules
+                        .get(schedule_id)
+                        .and_then(|schedule| self.get_next_due_slot(&schedule))
+                        .filter(|due_time| *due_time <= until)
+                    {
+                        due_schedules.push((due_time, schedule_id));
+                    }
+                }
+
+                due_schedules.sort_by_key(|(due_time, _)| *due_time);
+            }
+
+            due_schedules
+                .into_iter()
+                .take(limit)
+                .map(|(_, schedule_id)| schedule_id)
+                .collect()
+        }
+
+        /// Sets the tip paid, in the schedule's token, to a keeper other than the sender for
+        /// each trigger that pays due slots. A tip of 0 removes it, as does cancelling or
+        /// completing the schedule.
+        #[ink(message)]
+        pub fn set_keeper_tip(
+            &mut self,
+            schedule_id: Hash,
+            tip: Balance,
+        ) -> Result<(), TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            if schedule.sender != self.env().caller() {
+                return Err(TemporaError::Unauthorized);
+            }
+
+            // Streams are withdrawn by their recipient, not triggered
+            if schedule.stream.is_some() {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            if matches!(
+                schedule.status,
+                ScheduleStatus::Cancelled | ScheduleStatus::Completed
+            ) {
+                return Err(TemporaError::ScheduleConfigurationDisabled);
+            }
+
+            if tip == 0 {
+                self.remove_keeper_tip(&schedule_id);
+            } else {
+                self.keeper_tips.insert(schedule_id, &tip);
+
+                if !self.tipped_schedule_positions.contains(schedule_id) {
+                    let count = self.tipped_schedule_count.get().unwrap_or(0);
+
+                    self.tipped_schedules.insert(count, &schedule_id);
+                    self.tipped_schedule_positions.insert(schedule_id, &count);
+                    self.tipped_schedule_count.set(&(count + 1));
+                }
+            }
+
+            self.env().emit_event(KeeperTipChanged { schedule_id, tip });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_keeper_tip(&self, schedule_id: Hash) -> Balance {
+            self.keeper_tips.get(schedule_id).unwrap_or(0)
+        }
+
+        /// Looks through a page of the schedules with a keeper tip, and returns those that are
+        /// active and have slots due right now, along with their tip. At most `MAX_PAGE_SIZE`
+        /// schedules are looked through, so a page can return fewer than `limit` entries.
+        #[ink(message)]
+        pub fn get_due_schedules_with_tips(&self, offset: u32, limit: u32) -> Vec<(Hash, Balance)> {
+            let count = self.tipped_schedule_count.get().unwrap_or(0);
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+
+            (offset..end)
+                .filter_map(|position| self.tipped_schedules.get(position))
+                .filter(|schedule_id| {
+                    self.schedules
+                        .get(schedule_id)
+                        .is_some_and(|schedule| schedule.status == ScheduleStatus::Active)
+                        && matches!(self.get_due_amount(*schedule_id), Ok(amount) if amount > 0)
+                })
+                .map(|schedule_id| (schedule_id, self.get_keeper_tip(schedule_id)))
+                .collect()
+        }
+
+        /// Pays the caller what missed slots of an accumulating schedule have accrued to them.
+        ///
+        /// Slots whose tolerance window elapsed are accrued first, so claims do not depend on
+        /// the sender triggering. The protocol fees of accrued slots are collected before the
+        /// claim, which is drawn from the escrow when it covers it, and otherwise from the
+        /// sender's allowance.
+        #[ink(message)]
+        pub fn claim_accrued(&mut self, schedule_id: Hash) -> Result<Balance, TemporaError> {
+            self.ensure_not_paused()?;
+
+            let caller = self.env().caller();
+
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            if let Some(token_address) = schedule.token_address {
+                self.validate_token_is_whitelisted(token_address)?;
+            }
+
+            self.validate_token_is_not_frozen(&schedule)?;
+
+            if schedule.status == ScheduleStatus::Active
+                && schedule.missed_execution_policy == MissedExecutionPolicy::Accumulate
+            {
+                let now = self.current_timestamp_in_seconds();
+                let earliest_open_slot = now.saturating_sub(self.get_execution_tolerance());
+
+                let missed_slots: Vec<Timestamp> = self
+                    .get_slots_to_settle(&schedule, now)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|slot| *slot < earliest_open_slot)
+                    .collect();
+
+                if let Some(first_slot) = missed_slots.first() {
+                    let mut progress = self.schedule_progress.get(schedule_id).unwrap_or_default();
+                    let mut slot_index = self.get_slot_index(&schedule, *first_slot);
+
+                    for slot in missed_slots {
+                        self.accrue_slot(&schedule, slot);
+                        self.settle_slot(&schedule, slot, slot_index, &mut progress);
+
+                        slot_index = slot_index.saturating_add(1);
+                    }
+
+                    self.complete_if_finished(&schedule, &progress)?;
+
+                    self.index_due_schedule(&schedule_id);
+                }
+            }
+
+            let amount = self.get_accrued_amount(schedule_id, caller);
+
+            if amount == 0 {
+                return Ok(0);
+            }
+
+            self.collect_accrued_fees(&schedule)?;
+
+            let escrow_balance = self.get_escrow_balance(schedule_id);
+
+            if escrow_balance >= amount {
+                self.transfer_from_contract(schedule.token_address, caller, amount)?;
+
+                self.escrow_balances
+                    .insert(schedule_id, &(escrow_balance - amount));
+            } else if let Some(token_address) = schedule.token_address {
+                self.trigger_psp22_payment(schedule.sender, caller, amount, token_address)?;
+            } else {
+                return Err(TemporaError::InsufficientBalance);
+            }
+
+            self.accrued_amounts.remove((schedule_id, caller));
+
+            let accrued_total = self.accrued_totals.get(schedule_id).unwrap_or(0) - amount;
+            self.accrued_totals.insert(schedule_id, &accrued_total);
+
+            let receipt = self.new_payment_receipt(
+                &schedule,
+                caller,
+                amount,
+                self.current_timestamp_in_seconds(),
+                None,
+                PaymentOutcome::Success,
+            );
+            self.record_payment_execution(&schedule, receipt);
+
+            Ok(amount)
+        }
+
+        #[ink(message)]
+        pub fn get_accrued_amount(&self, schedule_id: Hash, account: AccountId) -> Balance {
+            self.accrued_amounts
+                .get((schedule_id, account))
+                .unwrap_or(0)
+        }
+
+        /// Returns whether the slot with the given index, counted from 0, has been paid or
+        /// accrued.
+        #[ink(message)]
+        pub fn is_slot_settled(&self, schedule_id: Hash, slot_index: u32) -> bool {
+            self.settled_slots.contains((schedule_id, slot_index))
+        }
+
+        #[ink(message)]
+        pub fn get_last_settled_slot_index(&self, schedule_id: Hash) -> Option<u32> {
+            self.last_settled_slot_indices.get(schedule_id)
+        }
+
+        pub fn trigger_native_payment(
+            &mut self,
+            recipient: AccountId,
+            amount: Balance,
+        ) -> Result<(), TemporaError> {
+            if self.env().transfer(recipient, amount).is_err() {
+                return Err(TemporaError::TransferError);
+            }
+
+            Ok(())
+        }
+
+        pub fn trigger_psp22_payment(
+            &mut self,
+            sender: AccountId,
+            recipient: AccountId,
+            amount: Balance,
+            token_address: AccountId,
+        ) -> Result<(), TemporaError> {
+            self.validate_token_is_whitelisted(token_address)?;
+
+            if PSP22Ref::transfer_from(&token_address, sender, recipient, amount, Vec::new())
+                .is_err()
+            {
+                return Err(TemporaError::TransferError);
+            }
+
+            Ok(())
+        }
+
+        /// Pays the due slots of a schedule, funding native payments that the escrow does not
+        /// cover with exactly `transferred_value`, and returns the outcome of the payment. The
+        /// caller must refund `transferred_value` when it failed. See `trigger_payment`.
+        fn trigger_schedule(
+            &mut self,
+            schedule_id: Hash,
+            transferred_value: Balance,
+        ) -> Result<PaymentOutcome, TemporaError> {
+            let schedule = self.get_schedule_by_id(&schedule_id)?;
+
+            self.validate_trigger_payment(&schedule, transferred_value)?;
+
+            let now = self.current_timestamp_in_seconds();
+
+            let slots = self.get_slots_to_settle(&schedule, now)?;
+
+            let earliest_open_slot = now.saturating_sub(self.get_execution_tolerance());
+
+            let (missed_slots, due_slots): (Vec<Timestamp>, Vec<Timestamp>) =
+                if schedule.missed_execution_policy == MissedExecutionPolicy::Accumulate {
+                    slots.iter().partition(|slot| **slot < earliest_open_slot)
+                } else {
+                    (Vec::new(), slots.clone())
+                };
+
+            if transferred_value > 0 && (due_slots.is_empty() || schedule.token_address.is_some()) {
+                return Err(TemporaError::UnexpectedTransferredValue);
+            }
+
+            let total_amount = schedule.amount.saturating_mul(due_slots.len() as u128);
+
+            let from_escrow = transferred_value == 0
+                && self.get_available_escrow_balance(&schedule) >= total_amount;
+
+            if !due_slots.is_empty()
+                && !from_escrow
+                && schedule.token_address.is_none()
+                && transferred_value != total_amount
+            {
+                return Err(TemporaError::InsufficientBalance);
+            }
+
+            let mut progress = self.schedule_progress.get(schedule_id).unwrap_or_default();
+            let mut slot_index = self.get_slot_index(&schedule, slots[0]);
+            let mut outcome = PaymentOutcome::Success;
+
+            for slot in missed_slots {
+                self.accrue_slot(&schedule, slot);
+                self.settle_slot(&schedule, slot, slot_index, &mut progress);
+
+                slot_index = slot_index.saturating_add(1);
+            }
+
+            if !due_slots.is_empty() {
+                match self.pay_schedule(&schedule, from_escrow, &due_slots) {
+                    Ok((payment_legs, fee)) => {
+                        for slot in due_slots {
+                            for (index, (recipient, amount)) in payment_legs.iter().enumerate() {
+                                let receipt = self.new_payment_receipt(
+                                    &schedule,
+                                    *recipient,
+                                    *amount,
+                                    slot,
+                                    Some(slot_index),
+                                    PaymentOutcome::Success,
+                                );
+
+                                // The fee is recorded with the first recipient paid
+                                self.record_payment_execution(
+                                    &schedule,
+                                    PaymentReceipt {
+                                        fee: if index == 0 { fee } else { 0 },
+                                        ..receipt
+                                    },
+                                );
+                            }
+
+                            self.settle_slot(&schedule, slot, slot_index, &mut progress);
+
+                            slot_index = slot_index.saturating_add(1);
+                        }
+
+                        self.pay_keeper_tip(&schedule);
+                    }
+                    // Only the sender's failures are recorded, so that other accounts cannot
+                    // fill the payment history with them
+                    Err(TemporaError::TransferError) if self.env().caller() != schedule.sender => {
+                        return Err(TemporaError::TransferError)
+                    }
+                    Err(TemporaError::TransferError) => {
+                        outcome = PaymentOutcome::Failed(TemporaError::TransferError);
+
+                        let receipt = self.new_payment_receipt(
+                            &schedule,
+                            schedule.recipient,
+                            total_amount,
+                            due_slots[0],
+                            Some(slot_index),
+                            outcome.clone(),
+                        );
+                        self.record_payment_execution(&schedule, receipt);
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+
+            self.complete_if_finished(&schedule, &progress)?;
+
+            self.index_due_schedule(&schedule_id);
+
+            Ok(outcome)
+        }
+
+        /// Returns the part of `remaining_value` a batch trigger spends on the schedule: its due
+        /// amount if it is a native payment the escrow does not cover, and nothing otherwise.
+        fn get_batch_trigger_value(&self, schedule_id: &Hash, remaining_value: Balance) -> Balance {
+            let Ok(schedule) = self.get_schedule_by_id(schedule_id) else {
+                return 0;
+            };
+
+            let due_amount = self.get_due_amount(*schedule_id).unwrap_or(0);
+
+            if schedule.token_address.is_some()
+                || self.get_available_escrow_balance(&schedule) >= due_amount
+                || remaining_value < due_amount
+            {
+                return 0;
+            }
+
+            due_amount
+        }
+
+        /// Transfers every leg of the payment of the given slots at once and returns the legs
+        /// paid for a single slot, along with the protocol fee deducted from it.
+        ///
+        /// The whole payment is gathered in the contract first: a PSP22 payment the escrow does
+        /// not cover is drawn from the sender's allowance in one transfer. If the funds cannot
+        /// be gathered it fails with `TransferError` before anything is paid. A leg the contract
+        /// then fails to pay out, such as to a recipient the token rejects, is kept in the
+        /// escrow and accrued to its recipient to claim, so a payment never stops halfway.
+        fn pay_schedule(
+            &mut self,
+            schedule: &ScheduleConfiguration,
+            from_escrow: bool,
+            slots: &[Timestamp],
+        ) -> Result<(Vec<(AccountId, Balance)>, Balance), TemporaError> {
+            let fee = self.get_payment_fee(schedule);
+            let slot_count = slots.len() as u128;
+
+            let payment_legs = self.get_payment_legs(schedule, schedule.amount - fee);
+
+            let legs_amount = payment_legs
+                .iter()
+                .fold(0, |total: Balance, (_, slot_amount)| {
+                    total.saturating_add(*slot_amount)
+                })
+                .saturating_mul(slot_count);
+
+            // Escrowed and transferred native funds are already held by the contract
+            match schedule.token_address {
+                Some(token_address) if !from_escrow => self.trigger_psp22_payment(
+                    schedule.sender,
+                    self.env().account_id(),
+                    legs_amount.saturating_add(fee.saturating_mul(slot_count)),
+                    token_address,
+                )?,
+                _ if !self.holds_funds(schedule.token_address, legs_amount) => {
+                    return Err(TemporaError::TransferError)
+                }
+                _ => {}
+            }
+
+            let mut paid_legs = Vec::new();
+            let mut held_amount: Balance = 0;
+
+            for (recipient, slot_amount) in payment_legs {
+                let amount = slot_amount.saturating_mul(slot_count);
+
+                if self
+                    .transfer_from_contract(schedule.token_address, recipient, amount)
+                    .is_ok()
+                {
+                    paid_legs.push((recipient, slot_amount));
+
+                    continue;
+                }
+
+                let accrued_amount = self.get_accrued_amount(schedule.id, recipient) + amount;
+
+                self.accrued_amounts
+                    .insert((schedule.id, recipient), &accrued_amount);
+
+                held_amount = held_amount.saturating_add(amount);
+
+                for slot in slots {
+                    self.env().emit_event(PaymentAccrued {
+                        schedule_id: schedule.id,
+                        recipient,
+                        amount: slot_amount,
+                        slot: *slot,
+                    });
+                }
+            }
+
+            // What was paid leaves the escrow, and what was held joins it
+            let escrow_balance = self.get_escrow_balance(schedule.id) + held_amount;
+            let escrow_balance = if from_escrow {
+                escrow_balance - schedule.amount.saturating_mul(slot_count)
+            } else {
+                escrow_balance
+            };
+
+            self.escrow_balances.insert(schedule.id, &escrow_balance);
+
+            if held_amount > 0 {
+                let accrued_total = self.accrued_totals.get(schedule.id).unwrap_or(0) + held_amount;
+
+                self.accrued_totals.insert(schedule.id, &accrued_total);
+            }
+
+            if fee > 0 {
+                let collected_fees = self.get_collected_fees(schedule.token_address)
+                    + fee.saturating_mul(slot_count);
+
+                self.collected_fees
+                    .insert(schedule.token_address, &collected_fees);
+            }
+
+            Ok((paid_legs, fee))
+        }
+
+        /// Whether the contract holds `amount` of the token, or of the native currency.
+        fn holds_funds(&self, token_address: Option<AccountId>, amount: Balance) -> bool {
+            match token_address {
+                Some(token_address) => {
+                    PSP22Ref::balance_of(&token_address, self.env().account_id()) >= amount
+                }
+                None => self.env().balance() >= amount,
+            }
+        }
+
+        /// Pays the keeper tip of the schedule to the caller from the escrow, or else from the
+        /// sender's allowance. A tip the sender's funds do not cover is not paid, and does not
+        /// affect the payment it rewards.
+        fn pay_keeper_tip(&mut self, schedule: &ScheduleConfiguration) {
+            let keeper = self.env().caller();
+            let tip = self.get_keeper_tip(schedule.id);
+
+            if tip == 0 || keeper == schedule.sender {
+                return;
+            }
+
+            if self.get_available_escrow_balance(schedule) >= tip {
+                if self
+                    .transfer_from_contract(schedule.token_address, keeper, tip)
+                    .is_err()
+                {
+                    return;
+                }
+
+                let balance = self.get_escrow_balance(schedule.id) - tip;
+                self.escrow_balances.insert(schedule.id, &balance);
+            } else if let Some(token_address) = schedule.token_address {
+                if self
+                    .trigger_psp22_payment(schedule.sender, keeper, tip, token_address)
+                    .is_err()
+                {
+                    return;
+                }
+            } else {
+                return;
+            }
+
+            self.env().emit_event(KeeperTipPaid {
+                schedule_id: schedule.id,
+                keeper,
+                token_address: schedule.token_address,
+                amount: tip,
+            });
+        }
+
+        /// Protocol fee of a single payment of the schedule, as of the fee version it accepted.
+        fn get_payment_fee(&self, schedule: &ScheduleConfiguration) -> Balance {
+            let version = self.schedule_fee_versions.get(schedule.id).unwrap_or(0);
+
+            self.get_fee_at_version(schedule, version)
+        }
+
+        fn get_fee_at_version(&self, schedule: &ScheduleConfiguration, version: u32) -> Balance {
+            self.protocol_fees.get(version).map_or(0, |protocol_fee| {
+                protocol_fee.get_amount(schedule.token_address, schedule.amount)
+            })
+        }
+
+        /// Splits `payment_amount` into the amount of each recipient of the schedule, starting
+        /// with the schedule's own recipient, who receives the remainder.
+        fn get_payment_legs(
+            &self,
+            schedule: &ScheduleConfiguration,
+            payment_amount: Balance,
+        ) -> Vec<(AccountId, Balance)> {
+            let mut payment_legs = Vec::new();
+            let mut remainder = payment_amount;
+
+            for split in schedule.splits.iter() {
+                let amount = split.get_amount(payment_amount);
+
+                remainder = remainder.saturating_sub(amount);
+
+                if amount > 0 {
+                    payment_legs.push((split.recipient, amount));
+                }
+            }
+
+            if remainder > 0 {
+                payment_legs.insert(0, (schedule.recipient, remainder));
+            }
+
+            payment_legs
+        }
+
+        fn withdraw_from_escrow(
+            &mut self,
+            schedule: &ScheduleConfiguration,
+            amount: Balance,
+        ) -> Result<(), TemporaError> {
+            let escrow_balance = self.get_escrow_balance(schedule.id);
+
+            if amount > escrow_balance {
+                return Err(TemporaError::InsufficientBalance);
+            }
+
+            self.transfer_from_contract(schedule.token_address, schedule.sender, amount)?;
+
+            let balance = escrow_balance - amount;
+
+            self.escrow_balances.insert(schedule.id, &balance);
+
+            self.env().emit_event(EscrowWithdrawn {
+                schedule_id: schedule.id,
+                sender: schedule.sender,
+                token_address: schedule.token_address,
+                amount,
+                balance,
+            });
+
+            Ok(())
+        }
+
+        /// Returns what a stream has accrued to its recipient and not been withdrawn yet.
+        fn get_stream_owed_amount(&self, schedule: &ScheduleConfiguration) -> Balance {
+            let Some(stream) = &schedule.stream else {
+                return 0;
+            };
+
+            let accrual_end = match stream.stop_time {
+                Some(stop_time) => stop_time.min(self.current_timestamp_in_seconds()),
+                None => self.current_timestamp_in_seconds(),
+            };
+
+            let accrued = stream
+                .rate_per_second
+                .saturating_mul(accrual_end.saturating_sub(stream.start_time).into());
+
+            accrued.saturating_sub(self.stream_withdrawals.get(schedule.id).unwrap_or(0))
+        }
+
+        /// Pays a stream's recipient what is owed, as far as its escrow covers.
+        fn settle_stream(
+            &mut self,
+            schedule: &ScheduleConfiguration,
+        ) -> Result<Balance, TemporaError> {
+            let amount = self
+                .get_stream_owed_amount(schedule)
+                .min(self.get_escrow_balance(schedule.id));
+
+            if amount > 0 {
+                self.transfer_from_contract(schedule.token_address, schedule.recipient, amount)?;
+
+                let balance = self.get_escrow_balance(schedule.id) - amount;
+                self.escrow_balances.insert(schedule.id, &balance);
+
+                let withdrawn = self.stream_withdrawals.get(schedule.id).unwrap_or(0) + amount;
+                self.stream_withdrawals.insert(schedule.id, &withdrawn);
+
+                let receipt = self.new_payment_receipt(
+                    schedule,
+                    schedule.recipient,
+                    amount,
+                    self.current_timestamp_in_seconds(),
+                    None,
+                    PaymentOutcome::Success,
+                );
+                self.record_payment_execution(schedule, receipt);
+            }
+
+            Ok(amount)
+        }
+
+        /// Returns `token_info` with the symbol and decimals the token provides through
+        /// `PSP22Metadata`. Accounts without code are left as they are.
+        fn read_token_metadata(
+            &self,
+            token_address: AccountId,
+            mut token_info: TokenInfo,
+        ) -> TokenInfo {
+            if !self.env().is_contract(&token_address) {
+                return token_info;
+            }
+
+            if let Ok(Ok(Some(symbol))) =
+                PSP22MetadataRef::token_symbol_builder(&token_address).try_invoke()
+            {
+                token_info.symbol = Some(symbol);
+            }
+
+            if let Ok(Ok(decimals)) =
+                PSP22MetadataRef::token_decimals_builder(&token_address).try_invoke()
+            {
+                token_info.decimals = decimals;
+            }
+
+            token_info
+        }
+
+        /// Transfers funds held by the contract, either native or in a PSP22 token.
+        fn transfer_from_contract(
+            &mut self,
+            token_address: Option<AccountId>,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), TemporaError> {
+            let transfer_result = match token_address {
+                Some(token_address) => {
+                    PSP22Ref::transfer(&token_address, to, amount, Vec::new()).is_ok()
+                }
+                None => self.env().transfer(to, amount).is_ok(),
+            };
+
+            if !transfer_result {
+                return Err(TemporaError::TransferError);
+            }
+
+            Ok(())
+        }
+
+        fn get_new_schedule_configuration(
+            &self,
+            new_schedule: NewSchedule,
+        ) -> ScheduleConfiguration {
+            ScheduleConfiguration {
+                id: new_schedule.id,
+                task_id: new_schedule.task_id,
+                sender: self.env().caller(),
+                recipient: new_schedule.recipient,
+                amount: new_schedule.amount,
+                token_address: new_schedule.token_address,
+                start_time: new_schedule.start_time,
+                interval: new_schedule.interval,
+                execution_times: new_schedule.execution_times,
+                recurrence: new_schedule.recurrence,
+                stream: None,
+                splits: new_schedule.splits,
+                end_conditions: new_schedule.end_conditions,
+                missed_execution_policy: new_schedule.missed_execution_policy,
+                status: ScheduleStatus::Active,
+            }
+        }
+
+        fn create_schedule(
+            &mut self,
+            new_schedule: ScheduleConfiguration,
+        ) -> Result<(), TemporaError> {
+            self.validate_schedule(&new_schedule, true)?;
+
+            self.schedules.insert(new_schedule.id, &new_schedule);
+
+            self.update_user_schedules(new_schedule.sender, &new_schedule.id);
+
+            self.update_recipients_schedules(&new_schedule);
+
+            self.update_token_schedules(&new_schedule);
+
+            self.index_due_schedule(&new_schedule.id);
+
+            let fee_version = self.get_protocol_fee_version();
+
+            if fee_version > 0 {
+                self.schedule_fee_versions
+                    .insert(new_schedule.id, &fee_version);
+            }
+
+            self.env().emit_event(ScheduleCreated {
+                schedule_id: new_schedule.id,
+                sender: new_schedule.sender,
+                recipient: new_schedule.recipient,
+                token_address: new_schedule.token_address,
+            });
+
+            Ok(())
+        }
+
+        fn validate_schedule(
+            &self,
+            schedule: &ScheduleConfiguration,
+            is_new: bool,
+        ) -> Result<(), TemporaError> {
+            if is_new && self.schedules.contains(schedule.id) {
+                return Err(TemporaError::ScheduleConfigurationAlreadyExists);
+            }
+
+            if schedule.sender == schedule.recipient {
+                return Err(TemporaError::CallerCannotBeRecipient);
+            }
+
+            if schedule.amount == 0 {
+                return Err(TemporaError::ScheduleAmountCannotBeZero);
+            }
+
+            if let Some(token_address) = schedule.token_address {
+                self.validate_token_is_whitelisted(token_address)?;
+
+                if schedule.stream.is_none() {
+                    self.validate_token_limits(token_address, schedule.amount)?;
+                }
+            }
+
+            self.validate_token_is_not_frozen(schedule)?;
+
+            let fee_version = if is_new {
+                self.get_protocol_fee_version()
+            } else {
+                self.schedule_fee_versions.get(schedule.id).unwrap_or(0)
+            };
+
+            self.validate_splits(schedule, self.get_fee_at_version(schedule, fee_version))?;
+
+            if let Some(stream) = &schedule.stream {
+                if !schedule.splits.is_empty()
+                    || schedule.start_time.is_some()
+                    || schedule.interval.is_some()
+                    || schedule.execution_times.is_some()
+                    || schedule.recurrence.is_some()
+                    || schedule.missed_execution_policy != MissedExecutionPolicy::Skip
+                    || stream.rate_per_second != schedule.amount
+                    || stream
+                        .stop_time
+                        .is_some_and(|stop_time| stop_time <= stream.start_time)
+                    || schedule.end_conditions != EndConditions::default()
+                {
+                    return Err(TemporaError::WrongScheduleConfiguration);
+                }
+
+                return Ok(());
+            }
+
+            if schedule.start_time.is_none() && schedule.execution_times.is_none() {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            if schedule.interval == Some(0) {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            // A recurrence rule replaces the interval and starts at `start_time`, and has to
+            // produce at least one slot
+            if let Some(recurrence) = &schedule.recurrence {
+                if schedule.interval.is_some()
+                    || schedule.execution_times.is_some()
+                    || !recurrence.is_valid()
+                    || !schedule.start_time.is_some_and(|start_time| {
+                        recurrence
+                            .occurrences_from(start_time, start_time)
+                            .next()
+                            .is_some()
+                    })
+                {
+                    return Err(TemporaError::WrongScheduleConfiguration);
+                }
+            }
+
+            if let Some(execution_times) = &schedule.execution_times {
+                if execution_times.is_empty()
+                    || execution_times.windows(2).any(|times| times[0] >= times[1])
+                {
+                    return Err(TemporaError::WrongScheduleConfiguration);
+                }
+            }
+
+            self.validate_end_conditions(schedule, is_new)
+        }
+
+        fn validate_end_conditions(
+            &self,
+            schedule: &ScheduleConfiguration,
+            is_new: bool,
+        ) -> Result<(), TemporaError> {
+            let end_conditions = &schedule.end_conditions;
+
+            let first_slot = match &schedule.execution_times {
+                Some(execution_times) => execution_times.first().copied(),
+                None => schedule.start_time,
+            };
+
+            if end_conditions.max_executions == Some(0)
+                || matches!(end_conditions.max_total_amount, Some(max) if max < schedule.amount)
+                || matches!(
+                    (end_conditions.end_time, first_slot),
+                    (Some(end_time), Some(first_slot)) if end_time < first_slot
+                )
+            {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            if is_new {
+                return Ok(());
+            }
+
+            // An update must leave something to pay
+            let progress = self.schedule_progress.get(schedule.id).unwrap_or_default();
+            let last_settled_slot = self.last_settled_slots.get(schedule.id);
+
+            if self.end_conditions_reached(schedule, &progress)
+                || matches!(
+                    (end_conditions.end_time, last_settled_slot),
+                    (Some(end_time), Some(last_settled_slot)) if end_time <= last_settled_slot
+                )
+            {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            Ok(())
+        }
+
+        /// Whether the execution count or the total amount limit leaves no further payment.
+        fn end_conditions_reached(
+            &self,
+            schedule: &ScheduleConfiguration,
+            progress: &ScheduleProgress,
+        ) -> bool {
+            let end_conditions = &schedule.end_conditions;
+
+            matches!(end_conditions.max_executions, Some(max) if progress.executions >= max)
+                || matches!(
+                    end_conditions.max_total_amount,
+                    Some(max) if progress.amount_paid.saturating_add(schedule.amount) > max
+                )
+        }
+
+        /// Checks that the splits fit in what a payment leaves after the protocol fee `fee`.
+        fn validate_splits(
+            &self,
+            schedule: &ScheduleConfiguration,
+            fee: Balance,
+        ) -> Result<(), TemporaError> {
+            if schedule.splits.len() > MAX_PAYMENT_SPLITS {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            let payment_amount = schedule.amount.saturating_sub(fee);
+            let mut split_recipients = Vec::<AccountId>::new();
+            let mut splits_amount: Balance = 0;
+
+            for split in schedule.splits.iter() {
+                if split.recipient == schedule.sender {
+                    return Err(TemporaError::CallerCannotBeRecipient);
+                }
+
+                if split.recipient == schedule.recipient
+                    || split_recipients.contains(&split.recipient)
+                {
+                    return Err(TemporaError::WrongScheduleConfiguration);
+                }
+
+                let is_valid_share = match split.share {
+                    SplitShare::Amount(amount) => amount > 0,
+                    SplitShare::BasisPoints(basis_points) => {
+                        basis_points > 0 && basis_points <= BASIS_POINTS_DENOMINATOR
+                    }
+                };
+
+                if !is_valid_share {
+                    return Err(TemporaError::WrongScheduleConfiguration);
+                }
+
+                splits_amount = splits_amount
+                    .checked_add(split.get_amount(payment_amount))
+                    .ok_or(TemporaError::WrongScheduleConfiguration)?;
+
+                split_recipients.push(split.recipient);
+            }
+
+            if splits_amount > payment_amount {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            Ok(())
+        }
+
+        fn validate_trigger_payment(
+            &self,
+            schedule: &ScheduleConfiguration,
+            transferred_value: Balance,
+        ) -> Result<(), TemporaError> {
+            if schedule.sender != self.env().caller() && transferred_value > 0 {
+                return Err(TemporaError::Unauthorized);
+            }
+
+            if schedule.status != ScheduleStatus::Active {
+                return Err(TemporaError::ScheduleConfigurationDisabled);
+            }
+
+            if schedule.stream.is_some() {
+                return Err(TemporaError::WrongScheduleConfiguration);
+            }
+
+            // Escrowed payments do not go through the whitelist check of the transfer
+            if self.is_schedule_delisted(schedule.id) {
+                return Err(TemporaError::TokenIsNotWhitelisted);
+            }
+
+            if let Some(token_address) = schedule.token_address {
+                self.validate_token_is_whitelisted(token_address)?;
+            }
+
+            self.validate_token_is_not_frozen(schedule)
+        }
+
+        fn token_is_whitelisted(&self, token_address: AccountId) -> bool {
+            self.token_registry
+                .get(token_address)
+                .is_some_and(|token_info| token_info.enabled)
+        }
+
+        fn validate_token_limits(
+            &self,
+            token_address: AccountId,
+            amount: Balance,
+        ) -> Result<(), TemporaError> {
+            let token_info = self.token_registry.get(token_address).unwrap_or_default();
+
+            if amount < token_info.min_amount
+                || token_info
+                    .max_amount
+                    .is_some_and(|max_amount| amount > max_amount)
+            {
+                return Err(TemporaError::AmountOutsideTokenLimits);
+            }
+
+            Ok(())
+        }
+
+        fn validate_token_is_whitelisted(
+            &self,
+            token_address: AccountId,
+        ) -> Result<(), TemporaError> {
+            if !self.token_is_whitelisted(token_address) {
+                return Err(TemporaError::TokenIsNotWhitelisted);
+            }
+
+            Ok(())
+        }
+
+        fn validate_token_is_not_frozen(
+            &self,
+            schedule: &ScheduleConfiguration,
+        ) -> Result<(), TemporaError> {
+            if schedule
+                .token_address
+                .is_some_and(|token_address| self.is_token_frozen(token_address))
+            {
+                return Err(TemporaError::TokenIsFrozen);
+            }
+
+            Ok(())
+        }
+
+        fn validate_user_schedule_exists(
+            &self,
+            caller: AccountId,
+            schedule_id: &Hash,
+        ) -> Result<(), TemporaError> {
+            match self.schedules.get(schedule_id) {
+                Some(schedule) => {
+                    if schedule.sender != caller {
+                        return Err(TemporaError::UserScheduleConfigurationNotFound);
+                    }
+                }
+                None => return Err(TemporaError::UserScheduleConfigurationNotFound),
+            }
+
+            Ok(())
+        }
+
+        fn get_user_schedule_data(&self, schedule_id: &Hash) -> UserScheduleData {
+            let mut schedule_configuration = self.schedules.get(schedule_id).unwrap();
+            schedule_configuration.status = self.get_current_status(&schedule_configuration);
+
+            let count = self.payment_execution_counts.get(schedule_id).unwrap_or(0);
+            let payment_executions = self.get_payment_executions(
+                *schedule_id,
+                count.saturating_sub(MAX_PAGE_SIZE),
+                MAX_PAGE_SIZE,
+            );
+
+            let progress = self.schedule_progress.get(schedule_id).unwrap_or_default();
+
+            UserScheduleData {
+                schedule_configuration,
+                payment_executions,
+                progress,
+            }
+        }
+
+        fn get_schedule_by_id(
+            &self,
+            schedule_id: &Hash,
+        ) -> Result<ScheduleConfiguration, TemporaError> {
+            match self.schedules.get(schedule_id) {
+                Some(schedule) => Ok(schedule),
+                None => Err(TemporaError::ScheduleConfigurationNotFound),
+            }
+        }
+
+        /// Returns the slots a trigger would settle now, oldest first: the missed slots unless
+        /// the policy is `Skip`, then the open slot if there is one. It is limited by
+        /// `MAX_SLOTS_PER_SETTLEMENT` and the end conditions.
+        fn get_slots_to_settle(
+            &self,
+            schedule: &ScheduleConfiguration,
+            now: Timestamp,
+        ) -> Result<Vec<Timestamp>, TemporaError> {
+            let earliest_open_slot = now.saturating_sub(self.get_execution_tolerance());
+            let latest_open_slot = now.saturating_add(self.get_execution_tolerance());
+
+            let from = match schedule.missed_execution_policy {
+                MissedExecutionPolicy::Skip => self.get_earliest_payable_slot(schedule, now),
+                _ => self
+                    .last_settled_slots
+                    .get(schedule.id)
+                    .map_or(0, |last_settled_slot| last_settled_slot.saturating_add(1)),
+            };
+
+            let mut slots = Vec::new();
+
+            for slot in self.get_slots_from(schedule, from, MAX_SLOTS_PER_SETTLEMENT) {
+                if slot > latest_open_slot {
+                    break;
+                }
+
+                slots.push(slot);
+
+                if slot >= earliest_open_slot {
+                    break;
+                }
+            }
+
+            if slots.is_empty() {
+                return Err(TemporaError::IncorrectExecutionTime);
+            }
+
+            let progress = self.schedule_progress.get(schedule.id).unwrap_or_default();
+
+            let mut slots: Vec<Timestamp> = slots
+                .into_iter()
+                .filter(|slot| self.is_before_end_time(schedule, *slot))
+                .collect();
+
+            slots.truncate(self.get_remaining_executions(schedule, &progress) as usize);
+
+            if slots.is_empty() {
+                return Err(TemporaError::EndConditionReached);
+            }
+
+            Ok(slots)
+        }
+
+        /// Returns how many more slots the execution count and total amount limits allow.
+        fn get_remaining_executions(
+            &self,
+            schedule: &ScheduleConfiguration,
+            progress: &ScheduleProgress,
+        ) -> u32 {
+            let end_conditions = &schedule.end_conditions;
+
+            let remaining_executions = end_conditions
+                .max_executions
+                .map_or(u32::MAX, |max| max.saturating_sub(progress.executions));
+            let remaining_payments = end_conditions.max_total_amount.map_or(u128::MAX, |max| {
+                max.saturating_sub(progress.amount_paid) / schedule.amount
+            });
+
+            remaining_executions.min(remaining_payments.try_into().unwrap_or(u32::MAX))
+        }
+
+        /// Returns the index of a slot in the sequence of slots of a schedule, counted from 0.
+        fn get_slot_index(&self, schedule: &ScheduleConfiguration, slot: Timestamp) -> u32 {
+            let last_settled = self
+                .last_settled_slots
+                .get(schedule.id)
+                .zip(self.last_settled_slot_indices.get(schedule.id))
+                .filter(|(last_settled_slot, _)| *last_settled_slot < slot);
+
+            let index = match (
+                &schedule.execution_times,
+                &schedule.recurrence,
+                schedule.start_time,
+                schedule.interval,
+            ) {
+                (Some(execution_times), _, _, _) => {
+                    execution_times.partition_point(|time| *time < slot) as u64
+                }
+                // Counting from the last settled slot keeps this bounded by the missed slots
+                (None, Some(recurrence), Some(start_time), _) => match last_settled {
+                    Some((last_settled_slot, last_settled_index)) => {
+                        let skipped = recurrence
+                            .occurrences_from(start_time, last_settled_slot.saturating_add(1))
+                            .take_while(|time| *time < slot)
+                            .count();
+
+                        u64::from(last_settled_index) + 1 + skipped as u64
+                    }
+                    None => recurrence
+                        .occurrences_from(start_time, start_time)
+                        .take_while(|time| *time < slot)
+                        .count() as u64,
+                },
+                (None, None, Some(start_time), Some(interval)) if interval > 0 => {
+                    slot.saturating_sub(start_time) / interval
+                }
+                _ => 0,
+            };
+
+            index.try_into().unwrap_or(u32::MAX)
+        }
+
+        /// Marks a slot as paid or accrued and counts it towards the end conditions.
+        fn settle_slot(
+            &mut self,
+            schedule: &ScheduleConfiguration,
+            slot: Timestamp,
+            slot_index: u32,
+            progress: &mut ScheduleProgress,
+        ) {
+            self.last_settled_slots.insert(schedule.id, &slot);
+            self.last_settled_slot_indices
+                .insert(schedule.id, &slot_index);
+            self.settled_slots.insert((schedule.id, slot_index), &slot);
+
+            progress.executions += 1;
+            progress.amount_paid += schedule.amount;
+
+            self.schedule_progress.insert(schedule.id, progress);
+        }
+
+        /// Credits every recipient with their part of a missed slot.
+        fn accrue_slot(&mut self, schedule: &ScheduleConfiguration, slot: Timestamp) {
+            let fee = self.get_payment_fee(schedule);
+
+            for (recipient, amount) in self.get_payment_legs(schedule, schedule.amount - fee) {
+                let accrued_amount = self.get_accrued_amount(schedule.id, recipient) + amount;
+
+                self.accrued_amounts
+                    .insert((schedule.id, recipient), &accrued_amount);
+
+                self.env().emit_event(PaymentAccrued {
+                    schedule_id: schedule.id,
+                    recipient,
+                    amount,
+                    slot,
+                });
+            }
+
+            if fee > 0 {
+                let accrued_fee = self.accrued_fees.get(schedule.id).unwrap_or(0) + fee;
+
+                self.accrued_fees.insert(schedule.id, &accrued_fee);
+            }
+
+            // The fee stays reserved with the recipients' amounts until it is collected
+            let accrued_total = self.accrued_totals.get(schedule.id).unwrap_or(0) + schedule.amount;
+
+            self.accrued_totals.insert(schedule.id, &accrued_total);
+        }
+
+        /// Moves the protocol fees of the schedule's accrued slots to the collected fees, drawn
+        /// from the escrow when it covers them and otherwise from the sender's allowance.
+        fn collect_accrued_fees(
+            &mut self,
+            schedule: &ScheduleConfiguration,
+        ) -> Result<(), TemporaError> {
+            let fee = self.accrued_fees.get(schedule.id).unwrap_or(0);
+
+            if fee == 0 {
+                return Ok(());
+            }
+
+            let escrow_balance = self.get_escrow_balance(schedule.id);
+
+            if escrow_balance >= fee {
+                self.escrow_balances
+                    .insert(schedule.id, &(escrow_balance - fee));
+            } else if let Some(token_address) = schedule.token_address {
+                self.trigger_psp22_payment(
+                    schedule.sender,
+                    self.env().account_id(),
+                    fee,
+                    token_address,
+                )?;
+            } else {
+                return Err(TemporaError::InsufficientBalance);
+            }
+
+            self.accrued_fees.remove(schedule.id);
+
+            let accrued_total = self.accrued_totals.get(schedule.id).unwrap_or(0) - fee;
+            self.accrued_totals.insert(schedule.id, &accrued_total);
+
+            let collected_fees = self.get_collected_fees(schedule.token_address) + fee;
+            self.collected_fees
+                .insert(schedule.token_address, &collected_fees);
+
+            Ok(())
+        }
+
+        fn complete_if_finished(
+            &mut self,
+            schedule: &ScheduleConfiguration,
+            progress: &ScheduleProgress,
+        ) -> Result<(), TemporaError> {
+            let Some(last_settled_slot) = self.last_settled_slots.get(schedule.id) else {
+                return Ok(());
+            };
+
+            if self.has_slots_after(schedule, last_settled_slot)
+                && !self.end_conditions_reached(schedule, progress)
+            {
+                return Ok(());
+            }
+
+            let mut schedule = self.get_schedule_by_id(&schedule.id)?;
+
+            self.update_schedule_status(&mut schedule, ScheduleStatus::Completed)
+        }
+
+        /// Returns the escrow balance that is not owed to the recipients yet.
+        fn get_available_escrow_balance(&self, schedule: &ScheduleConfiguration) -> Balance {
+            self.get_escrow_balance(schedule.id)
+                .saturating_sub(self.accrued_totals.get(schedule.id).unwrap_or(0))
+        }
+
+        /// Returns the time from which slots are neither settled nor past their tolerance window.
+        fn get_earliest_payable_slot(
+            &self,
+            schedule: &ScheduleConfiguration,
+            now: Timestamp,
+        ) -> Timestamp {
+            let earliest_open_slot = now.saturating_sub(self.get_execution_tolerance());
+
+            match self.last_settled_slots.get(schedule.id) {
+                Some(last_settled_slot) => {
+                    earliest_open_slot.max(last_settled_slot.saturating_add(1))
+                }
+                None => earliest_open_slot,
+            }
+        }
+
+        /// Returns up to `limit` slots of a schedule that are not before `from`, in order.
+        ///
+        /// Slots are taken from `execution_times` when present, otherwise from the recurrence
+        /// rule or from `start_time` plus multiples of `interval` (a single slot if there is
+        /// neither).
+        fn get_slots_from(
+            &self,
+            schedule: &ScheduleConfiguration,
+            from: Timestamp,
+            limit: usize,
+        ) -> Vec<Timestamp> {
+            match (
+                &schedule.execution_times,
+                &schedule.recurrence,
+                schedule.start_time,
+                schedule.interval,
+            ) {
+                (Some(execution_times), _, _, _) => execution_times
+                    .iter()
+                    .copied()
+                    .filter(|time| *time >= from)
+                    .take(limit)
+                    .collect(),
+                (None, Some(recurrence), Some(start_time), _) => recurrence
+                    .occurrences_from(start_time, from)
+                    .take(limit)
+                    .collect(),
+                (None, None, Some(start_time), Some(interval)) if interval > 0 => {
+                    let first_index = from.saturating_sub(start_time).div_ceil(interval);
+
+                    (first_index..)
+                        .map_while(|index| {
+                            index
+                                .checked_mul(interval)
+                                .and_then(|offset| start_time.checked_add(offset))
+                        })
+                        .take(limit)
+                        .collect()
+                }
+                (None, None, Some(start_time), None) => Some(start_time)
+                    .filter(|time| *time >= from)
+                    .into_iter()
+                    .take(limit)
+                    .collect(),
+                _ => Vec::new(),
+            }
+        }
+
+        fn is_before_end_time(&self, schedule: &ScheduleConfiguration, slot: Timestamp) -> bool {
+            !matches!(schedule.end_conditions.end_time, Some(end_time) if slot > end_time)
+        }
+
+        fn has_slots_after(&self, schedule: &ScheduleConfiguration, slot: Timestamp) -> bool {
+            matches!(
+                self.get_slots_from(schedule, slot.saturating_add(1), 1).first(),
+                Some(next_slot) if self.is_before_end_time(schedule, *next_slot)
+            )
+        }
+
+        /// Returns the stored status, or `Expired` for an active schedule that has no slot
+        /// left that could still be paid.
+        fn get_current_status(&self, schedule: &ScheduleConfiguration) -> ScheduleStatus {
+            if schedule.status != ScheduleStatus::Active || schedule.stream.is_some() {
+                return schedule.status;
+            }
+
+            let from =
+                self.get_earliest_payable_slot(schedule, self.current_timestamp_in_seconds());
+
+            match self.get_slots_from(schedule, from, 1).first() {
+                Some(slot) if self.is_before_end_time(schedule, *slot) => schedule.status,
+                _ => ScheduleStatus::Expired,
+            }
+        }
+
+        fn update_schedule_status(
+            &mut self,
+            schedule: &mut ScheduleConfiguration,
+            status: ScheduleStatus,
+        ) -> Result<(), TemporaError> {
+            let previous_status = schedule.status;
+
+            if !previous_status.can_transition_to(status) {
+                return Err(TemporaError::InvalidScheduleStatusTransition);
+            }
+
+            schedule.status = status;
+
+            self.schedules.insert(schedule.id, schedule);
+
+            self.index_due_schedule(&schedule.id);
+
+            if matches!(
+                status,
+                ScheduleStatus::Cancelled | ScheduleStatus::Completed
+            ) {
+                self.remove_token_schedule(schedule);
+                self.remove_keeper_tip(&schedule.id);
+            }
+
+            self.env().emit_event(ScheduleStatusChanged {
+                schedule_id: schedule.id,
+                sender: schedule.sender,
+                recipient: schedule.recipient,
+                previous_status,
+                status,
+            });
+
+            Ok(())
+        }
+
+        /// Returns the first slot that can still be triggered, if the schedule can still pay it.
+        ///
+        /// For `Skip` schedules this is the first slot not yet past its tolerance window, like
+        /// the slots settled by a trigger; otherwise the first slot after the last settled one.
+        fn get_next_due_slot(&self, schedule: &ScheduleConfiguration) -> Option<Timestamp> {
+            if schedule.status != ScheduleStatus::Active || schedule.stream.is_some() {
+                return None;
+            }
+
+            let progress = self.schedule_progress.get(schedule.id).unwrap_or_default();
+
+            if self.get_remaining_executions(schedule, &progress) == 0 {
+                return None;
+            }
+
+            let from = if schedule.missed_execution_policy == MissedExecutionPolicy::Skip {
+                self.get_earliest_payable_slot(schedule, self.current_timestamp_in_seconds())
+            } else {
+                self.last_settled_slots
+                    .get(schedule.id)
+                    .map_or(0, |last_settled_slot| last_settled_slot.saturating_add(1))
+            };
+
+            self.get_slots_from(schedule, from, 1)
+                .first()
+                .copied()
+                .filter(|slot| self.is_before_end_time(schedule, *slot))
+        }
+
+        /// Moves the schedule to the bucket of its next due slot in the due schedules index,
+        /// or out of the index if it has none.
+        fn index_due_schedule(&mut self, schedule_id: &Hash) {
+            let next_due_slot = self
+                .schedules
+                .get(schedule_id)
+                .and_then(|schedule| self.get_next_due_slot(&schedule));
+            let indexed_due_time = self.schedule_due_times.get(schedule_id);
+
+            if next_due_slot == indexed_due_time {
+                return;
+            }
+
+            if let Some(due_time) = indexed_due_time {
+                self.remove_from_due_bucket(schedule_id, due_time / DUE_BUCKET_DURATION);
+                self.schedule_due_times.remove(schedule_id);
+            }
+
+            if let Some(due_time) = next_due_slot {
+                let bucket = due_time / DUE_BUCKET_DURATION;
+                let bucket_size = self.due_bucket_sizes.get(bucket).unwrap_or(0);
+
+                if bucket_size == 0 {
+                    let bucket_count = self.due_bucket_count.get().unwrap_or(0);
+
+                    self.due_buckets.insert(bucket_count, &bucket);
+                    self.due_bucket_positions.insert(bucket, &bucket_count);
+                    self.due_bucket_count.set(&(bucket_count + 1));
+                }
+
+                self.due_schedules
+                    .insert((bucket, bucket_size), schedule_id);
+                self.schedule_due_positions
+                    .insert(schedule_id, &bucket_size);
+                self.due_bucket_sizes.insert(bucket, &(bucket_size + 1));
+                self.schedule_due_times.insert(schedule_id, &due_time);
+            }
+        }
+
+        /// Removes the schedule from a due bucket, moving the last schedule of the bucket into
+        /// its position, and drops the bucket once it is empty.
+        fn remove_from_due_bucket(&mut self, schedule_id: &Hash, bucket: u64) {
+            let Some(position) = self.schedule_due_positions.get(schedule_id) else {
+                return;
+            };
+
+            let last_position = self
+                .due_bucket_sizes
+                .get(bucket)
+                .unwrap_or(0)
+                .saturating_sub(1);
+
+            if position != last_position {
+                if let Some(last_schedule_id) = self.due_schedules.get((bucket, last_position)) {
+                    self.due_schedules
+                        .insert((bucket, position), &last_schedule_id);
+                    self.schedule_due_positions
+                        .insert(last_schedule_id, &position);
+                }
+            }
+
+            self.due_schedules.remove((bucket, last_position));
+            self.schedule_due_positions.remove(schedule_id);
+
+            if last_position > 0 {
+                self.due_bucket_sizes.insert(bucket, &last_position);
+                return;
+            }
+
+            self.due_bucket_sizes.remove(bucket);
+
+            let Some(bucket_position) = self.due_bucket_positions.get(bucket) else {
+                return;
+            };
+
+            let last_bucket_position = self.due_bucket_count.get().unwrap_or(0).saturating_sub(1);
+
+            if bucket_position != last_bucket_position {
+                if let Some(last_bucket) = self.due_buckets.get(last_bucket_position) {
+                    self.due_buckets.insert(bucket_position, &last_bucket);
+                    self.due_bucket_positions
+                        .insert(last_bucket, &bucket_position);
+                }
+            }
+
+            self.due_buckets.remove(last_bucket_position);
+            self.due_bucket_positions.remove(bucket);
+            self.due_bucket_count.set(&last_bucket_position);
+        }
+
+        fn current_timestamp_in_seconds(&self) -> Timestamp {
+            self.env().block_timestamp().checked_div(1000).unwrap()
+        }
+
+        /// Returns the receipt of a payment attempt of the schedule made now by the caller,
+        /// without a protocol fee.
+        fn new_payment_receipt(
+            &self,
+            schedule: &ScheduleConfiguration,
+            recipient: AccountId,
+            amount: Balance,
+            slot: Timestamp,
+            slot_index: Option<u32>,
+            outcome: PaymentOutcome,
+        ) -> PaymentReceipt {
+            PaymentReceipt {
+                receipt_id: 0,
+                schedule_id: schedule.id,
+                slot,
+                slot_index,
+                timestamp: self.current_timestamp_in_seconds(),
+                block_number: self.env().block_number(),
+                executor: self.env().caller(),
+                recipient,
+                amount,
+                fee: 0,
+                token_address: schedule.token_address,
+                outcome,
+            }
+        }
+
+        /// Stores the receipt of a payment attempt and emits `PaymentExecuted` or
+        /// `PaymentFailed` depending on its outcome.
+        fn record_payment_execution(
+            &mut self,
+            schedule: &ScheduleConfiguration,
+            receipt: PaymentReceipt,
+        ) {
+            let receipt_id = self.store_payment_receipt(receipt.clone());
+
+            match receipt.outcome {
+                PaymentOutcome::Success => self.env().emit_event(PaymentExecuted {
+                    schedule_id: schedule.id,
+                    sender: schedule.sender,
+                    recipient: receipt.recipient,
+                    token_address: schedule.token_address,
+                    amount: receipt.amount,
+                    fee: receipt.fee,
+                    slot: receipt.slot,
+                    receipt_id,
+                }),
+                PaymentOutcome::Failed(reason) => self.env().emit_event(PaymentFailed {
+                    schedule_id: schedule.id,
+                    sender: schedule.sender,
+                    recipient: receipt.recipient,
+                    token_address: schedule.token_address,
+                    amount: receipt.amount,
+                    slot: receipt.slot,
+                    reason,
+                    receipt_id,
+                }),
+            }
+        }
+
+        /// Appends a receipt to the history of its schedule under the next receipt id, which
+        /// is returned.
+        fn store_payment_receipt(&mut self, receipt: PaymentReceipt) -> u64 {
+            let receipt_id = self.next_receipt_id.get().unwrap_or(0);
+            self.next_receipt_id.set(&(receipt_id + 1));
+
+            let schedule_id = receipt.schedule_id;
+            let position = self.payment_execution_counts.get(schedule_id).unwrap_or(0);
+
+            self.receipt_locations
+                .insert(receipt_id, &(schedule_id, position));
+            self.payment_execution_counts
+                .insert(schedule_id, &position.saturating_add(1));
+            self.payment_executions.insert(
+                (schedule_id, position),
+                &PaymentReceipt {
+                    receipt_id,
+                    ..receipt
+                },
+            );
+
+            receipt_id
+        }
+
+        fn update_user_schedules(&mut self, user_account_id: AccountId, schedule_id: &Hash) {
+            let mut user_schedules = self.user_schedules.get(user_account_id).unwrap_or_default();
+
+            if user_schedules.contains(schedule_id) {
+                return;
+            }
+
+            user_schedules.push(*schedule_id);
+            self.user_schedules.insert(user_account_id, &user_schedules);
+        }
+
+        fn update_token_schedules(&mut self, schedule: &ScheduleConfiguration) {
+            let Some(token_address) = schedule.token_address else {
+                return;
+            };
+
+            if matches!(
+                schedule.status,
+                ScheduleStatus::Cancelled | ScheduleStatus::Completed
+            ) || self.token_schedule_positions.contains(schedule.id)
+            {
+                return;
+            }
+
+            let count = self.token_schedule_counts.get(token_address).unwrap_or(0);
+
+            self.token_schedules
+                .insert((token_address, count), &schedule.id);
+            self.token_schedule_positions.insert(schedule.id, &count);
+            self.token_schedule_counts
+                .insert(token_address, &(count + 1));
+        }
+
+        /// Takes the schedule out of the index of its token, moving the last schedule of the
+        /// token into its position.
+        fn remove_token_schedule(&mut self, schedule: &ScheduleConfiguration) {
+            let Some(token_address) = schedule.token_address else {
+                return;
+            };
+
+            let Some(position) = self.token_schedule_positions.get(schedule.id) else {
+                return;
+            };
+
+            let last_position = self
+                .token_schedule_counts
+                .get(token_address)
+                .unwrap_or(0)
+                .saturating_sub(1);
+
+            if position != last_position {
+                if let Some(last_schedule_id) =
+                    self.token_schedules.get((token_address, last_position))
+                {
+                    self.token_schedules
+                        .insert((token_address, position), &last_schedule_id);
+                    self.token_schedule_positions
+                        .insert(last_schedule_id, &position);
+                }
+            }
+
+            self.token_schedules.remove((token_address, last_position));
+            self.token_schedule_positions.remove(schedule.id);
+            self.token_schedule_counts
+                .insert(token_address, &last_position);
+        }
+
+        /// Removes the keeper tip of the schedule, moving the last tipped schedule into its
+        /// position in the index.
+        fn remove_keeper_tip(&mut self, schedule_id: &Hash) {
+            let Some(position) = self.tipped_schedule_positions.get(schedule_id) else {
+                return;
+            };
+
+            let last_position = self
+                .tipped_schedule_count
+                .get()
+                .unwrap_or(0)
+                .saturating_sub(1);
+
+            if position != last_position {
+                if let Some(last_schedule_id) = self.tipped_schedules.get(last_position) {
+                    self.tipped_schedules.insert(position, &last_schedule_id);
+                    self.tipped_schedule_positions
+                        .insert(last_schedule_id, &position);
+                }
+            }
+
+            self.tipped_schedules.remove(last_position);
+            self.tipped_schedule_positions.remove(schedule_id);
+            self.tipped_schedule_count.set(&last_position);
+            self.keeper_tips.remove(schedule_id);
+        }
+
+        /// Flags up to `MAX_SCHEDULES_PER_BATCH` schedules of a delisted token, taking them out
+        /// of its index, and returns how many are left in it.
+        fn flag_next_delisted_schedules(&mut self, token_address: AccountId) -> u32 {
+            let mut count = self.token_schedule_counts.get(token_address).unwrap_or(0);
+            let remaining = count.saturating_sub(MAX_SCHEDULES_PER_BATCH as u32);
+
+            while count > remaining {
+                count -= 1;
+
+                if let Some(schedule_id) = self.token_schedules.get((token_address, count)) {
+                    self.token_schedules.remove((token_address, count));
+                    self.token_schedule_positions.remove(schedule_id);
+
+                    self.flag_delisted_schedule(&schedule_id, token_address);
+                }
+            }
+
+            self.token_schedule_counts.insert(token_address, &count);
+
+            count
+        }
+
+        fn flag_delisted_schedule(&mut self, schedule_id: &Hash, token_address: AccountId) {
+            let Some(schedule) = self.schedules.get(schedule_id) else {
+                return;
+            };
+
+            self.delisted_schedules.insert(schedule_id, &());
+
+            self.env().emit_event(ScheduleTokenDelisted {
+                schedule_id: *schedule_id,
+                sender: schedule.sender,
+                token_address,
+            });
+        }
+
+        fn update_recipients_schedules(&mut self, schedule: &ScheduleConfiguration) {
+            self.update_user_schedules(schedule.recipient, &schedule.id);
+
+            for split in schedule.splits.iter() {
+                self.update_user_schedules(split.recipient, &schedule.id);
+            }
+        }
+
+        /// Converts the storage from `from_version` to the next layout version.
+        fn migrate_from(&mut self, from_version: u32) -> Result<(), TemporaError> {
+            match from_version {
+                // The first deployed layout kept no execution tolerance. Its schedules are
+                // converted by `migrate_legacy_schedules`
+                0 => {
+                    self.execution_tolerance.set(&DEFAULT_EXECUTION_TOLERANCE);
+
+                    Ok(())
+                }
+                // Roles replace the single admin, who is granted all of them
+                1 => {
+                    for role in Role::ALL {
+                        self.add_role_member(role, self.admin);
+                    }
+
+                    Ok(())
+                }
+                // The token registry replaces the bare whitelist
+                2 => {
+                    for token_address in self.tokens_whitelist.iter() {
+                        self.token_registry
+                            .insert(token_address, &TokenInfo::default());
+                    }
+
+                    Ok(())
+                }
+                _ => Err(TemporaError::UnsupportedStorageVersion),
+            }
+        }
+
+        /// Converts a schedule stored in the layout of the first deployed version, which
+        /// recorded the time of each payment but not the slot it paid. The slot open at the
+        /// last payment is settled so it cannot be paid twice.
+        fn migrate_legacy_schedule(&mut self, schedule_id: Hash) -> Result<(), TemporaError> {
+            let schedule_key = (self.schedules.key(), schedule_id);
+
+            let Some(stored_size) = ink::env::contains_contract_storage(&schedule_key) else {
+                return Err(TemporaError::ScheduleConfigurationNotFound);
+            };
+
+            // A schedule in the current layout is longer than its legacy prefix
+            let legacy_schedule =
+                match ink::env::get_contract_storage::<_, LegacyScheduleConfiguration>(
+                    &schedule_key,
+                ) {
+                    Ok(Some(legacy_schedule))
+                        if legacy_schedule.encoded_size() as u32 == stored_size =>
+                    {
+                        legacy_schedule
+                    }
+                    _ => return Ok(()),
+                };
+
+            let schedule = ScheduleConfiguration {
+                id: schedule_id,
+                task_id: legacy_schedule.task_id,
+                sender: legacy_schedule.sender,
+                recipient: legacy_schedule.recipient,
+                amount: legacy_schedule.amount,
+                token_address: legacy_schedule.token_address,
+                start_time: legacy_schedule.start_time,
+                interval: legacy_schedule.interval,
+                execution_times: legacy_schedule.execution_times,
+                recurrence: None,
+                stream: None,
+                splits: Vec::new(),
+                end_conditions: EndConditions::default(),
+                missed_execution_policy: MissedExecutionPolicy::default(),
+                status: if legacy_schedule.enabled {
+                    ScheduleStatus::Active
+                } else {
+                    ScheduleStatus::Cancelled
+                },
+            };
+
+            self.schedules.insert(schedule_id, &schedule);
+
+            let executions_key = (self.payment_executions.key(), schedule_id);
+            let payment_times: Vec<Timestamp> = ink::env::get_contract_storage(&executions_key)
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+
+            ink::env::clear_contract_storage(&executions_key);
+
+            for payment_time in payment_times.iter() {
+                self.store_payment_receipt(PaymentReceipt {
+                    receipt_id: 0,
+                    schedule_id,
+                    slot: *payment_time,
+                    slot_index: None,
+                    timestamp: *payment_time,
+                    block_number: 0,
+                    executor: schedule.sender,
+                    recipient: schedule.recipient,
+                    amount: schedule.amount,
+                    fee: 0,
+                    token_address: schedule.token_address,
+                    outcome: PaymentOutcome::Success,
+                });
+            }
+
+            self.schedule_progress.insert(
+                schedule_id,
+                &ScheduleProgress {
+                    executions: payment_times.len() as u32,
+                    amount_paid: schedule.amount.saturating_mul(payment_times.len() as u128),
+                },
+            );
+
+            if let Some(last_payment_time) = payment_times.last() {
+                let tolerance = self.get_execution_tolerance();
+
+                let paid_slot = self
+                    .get_slots_from(&schedule, last_payment_time.saturating_sub(tolerance), 1)
+                    .into_iter()
+                    .find(|slot| *slot <= last_payment_time.saturating_add(tolerance));
+
+                if let Some(slot) = paid_slot {
+                    let slot_index = self.get_slot_index(&schedule, slot);
+
+                    self.last_settled_slots.insert(schedule_id, &slot);
+                    self.last_settled_slot_indices
+                        .insert(schedule_id, &slot_index);
+                    self.settled_slots.insert((schedule_id, slot_index), &slot);
+                }
+            }
+
+            self.update_token_schedules(&schedule);
+
+            self.index_due_schedule(&schedule_id);
+
+            Ok(())
+        }
+
+        fn ensure_admin(&self) -> Result<(), TemporaError> {
+            if self.env().caller() != self.admin {
+                return Err(TemporaError::Unauthorized);
+            }
+
+            Ok(())
+        }
+
+        fn ensure_role(&self, role: Role) -> Result<(), TemporaError> {
+            if !self.has_role(role, self.env().caller()) {
+                return Err(TemporaError::Unauthorized);
+            }
+
+            Ok(())
+        }
+
+        fn ensure_not_paused(&self) -> Result<(), TemporaError> {
+            if self.is_paused() {
+                return Err(TemporaError::ContractPaused);
+            }
+
+            Ok(())
+        }
+
+        /// Returns whether the account was added, that is, did not hold the role yet.
+        fn add_role_member(&mut self, role: Role, account: AccountId) -> bool {
+            let mut members = self.get_role_members(role);
+
+            if members.contains(&account) {
+                return false;
+            }
+
+            members.push(account);
+
+            self.role_members.insert(role, &members);
+
+            true
+        }
+
+        /// Returns whether the account was removed, that is, held the role.
+        fn remove_role_member(&mut self, role: Role, account: AccountId) -> bool {
+            let mut members = self.get_role_members(role);
+
+            let Some(index) = members.iter().position(|member| *member == account) else {
+                return false;
+            };
+
+            members.remove(index);
+
+            self.role_members.insert(role, &members);
+
+            true
+        }
+
+        fn remove_role(&mut self, role: Role, account: AccountId) -> Result<(), TemporaError> {
+            // The admin hands `SuperAdmin` over through `accept_admin`, so there is always one
+            if role == Role::SuperAdmin && account == self.admin {
+                return Err(TemporaError::CannotRevokeAdmin);
+            }
+
+            if !self.remove_role_member(role, account) {
+                return Err(TemporaError::RoleNotGranted);
+            }
+
+            self.env().emit_event(RoleRevoked {
+                role,
+                account,
+                revoked_by: self.env().caller(),
+            });
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::recurrence::{Frequency, Weekday, WeekdayRule};
+        use ink::env::test::{
+            default_accounts, get_account_balance, recorded_events, set_account_balance,
+            set_block_timestamp, set_callee, set_caller, transfer_in, DefaultAccounts,
+        };
+        use ink::env::DefaultEnvironment;
+        use ink::primitives::AccountId;
+
+        fn get_default_accounts() -> DefaultAccounts<DefaultEnvironment> {
+            default_accounts::<DefaultEnvironment>()
+        }
+
+        fn set_sender(sender: AccountId) {
+            set_caller::<DefaultEnvironment>(sender);
+        }
+
+        fn set_value_to_transfer(amount: u128) {
+            transfer_in::<DefaultEnvironment>(amount);
+        }
+
+        fn set_timestamp_in_seconds(seconds: u64) {
+            set_block_timestamp::<DefaultEnvironment>(seconds * 1000);
+        }
+
+        fn get_balance(account: AccountId) -> u128 {
+            get_account_balance::<DefaultEnvironment>(account).unwrap()
+        }
+
+        type Event = <TemporaContract as ink::reflect::ContractEventBase>::Type;
+
+        fn get_emitted_events() -> Vec<Event> {
+            recorded_events()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect()
+        }
+
+        fn init() -> (TemporaContract, DefaultAccounts<DefaultEnvironment>) {
+            let accounts = get_default_accounts();
+
+            let contract_account = AccountId::from([0xC; 32]);
+
+            set_account_balance::<DefaultEnvironment>(accounts.alice, 1_000_000_000);
+            set_account_balance::<DefaultEnvironment>(contract_account, 0);
+            set_callee::<DefaultEnvironment>(contract_account);
+
+            (TemporaContract::new(), accounts)
+        }
+
+        #[ink::test]
+        fn propose_admin_works() {
+            let (mut contract, accounts) = init();
+
+            set_timestamp_in_seconds(10000);
+            let _ = contract.propose_admin(accounts.bob);
+
+            assert_eq!(contract.admin, accounts.alice);
+            assert_eq!(
+                contract.get_pending_admin(),
+                Some(PendingAdmin {
+                    account: accounts.bob,
+                    expires_at: 10000 + ADMIN_PROPOSAL_DURATION,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn propose_admin_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.propose_admin(accounts.bob);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn accept_admin_works() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.propose_admin(accounts.bob);
+
+            set_sender(accounts.bob);
+            let result = contract.accept_admin();
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.admin, accounts.bob);
+            assert_eq!(contract.get_pending_admin(), None);
+        }
+
+        #[ink::test]
+        fn accept_admin_fails() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.propose_admin(accounts.bob);
+
+            set_sender(accounts.charlie);
+            let result = contract.accept_admin();
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+            assert_eq!(contract.admin, accounts.alice);
+        }
+
+        #[ink::test]
+        fn accept_expired_admin_proposal_fails() {
+            let (mut contract, accounts) = init();
+
+            set_timestamp_in_seconds(10000);
+            let _ = contract.propose_admin(accounts.bob);
+
+            set_timestamp_in_seconds(10000 + ADMIN_PROPOSAL_DURATION + 1);
+            set_sender(accounts.bob);
+            let result = contract.accept_admin();
+
+            assert_eq!(result, Err(TemporaError::AdminProposalExpired));
+            assert_eq!(contract.admin, accounts.alice);
+        }
+
+        #[ink::test]
+        fn cancel_admin_proposal_works() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.propose_admin(accounts.bob);
+            let cancel_result = contract.cancel_admin_proposal();
+
+            set_sender(accounts.bob);
+            let accept_result = contract.accept_admin();
+
+            assert_eq!(cancel_result, Ok(()));
+            assert_eq!(contract.get_pending_admin(), None);
+            assert_eq!(accept_result, Err(TemporaError::NoPendingAdmin));
+        }
+
+        #[ink::test]
+        fn cancel_admin_proposal_fails() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.propose_admin(accounts.bob);
+
+            set_sender(accounts.bob);
+            let result = contract.cancel_admin_proposal();
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+            assert!(contract.get_pending_admin().is_some());
+        }
+
+        #[ink::test]
+        fn set_code_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.set_code([0x1; 32]);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn migrate_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.migrate();
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn migrate_from_newer_storage_version_fails() {
+            let (mut contract, _) = init();
+
+            contract.storage_version.set(&(STORAGE_VERSION + 1));
+            let result = contract.migrate();
+
+            assert_eq!(result, Err(TemporaError::UnsupportedStorageVersion));
+        }
+
+        #[ink::test]
+        fn grant_role_works() {
+            let (mut contract, accounts) = init();
+
+            let result = contract.grant_role(Role::WhitelistManager, accounts.bob);
+
+            assert_eq!(result, Ok(()));
+            assert!(contract.has_role(Role::WhitelistManager, accounts.bob));
+            assert_eq!(
+                contract.get_role_members(Role::WhitelistManager),
+                vec![accounts.alice, accounts.bob]
+            );
+
+            let emitted_events = get_emitted_events();
+
+            assert!(matches!(
+                &emitted_events[0],
+                Event::RoleGranted(event)
+                    if event.role == Role::WhitelistManager
+                        && event.account == accounts.bob
+                        && event.granted_by == accounts.alice
+            ));
+        }
+
+        #[ink::test]
+        fn grant_role_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.grant_role(Role::WhitelistManager, accounts.bob);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn grant_role_twice_fails() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.grant_role(Role::Pauser, accounts.bob);
+            let result = contract.grant_role(Role::Pauser, accounts.bob);
+
+            assert_eq!(result, Err(TemporaError::RoleAlreadyGranted));
+        }
+
+        #[ink::test]
+        fn whitelist_manager_cannot_propose_admin() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.grant_role(Role::WhitelistManager, accounts.bob);
+
+            set_sender(accounts.bob);
+            let whitelist_result = contract.add_token_to_whitelist(accounts.charlie);
+            let propose_admin_result = contract.propose_admin(accounts.bob);
+
+            assert_eq!(whitelist_result, Ok(()));
+            assert_eq!(propose_admin_result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn revoke_role_works() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.grant_role(Role::WhitelistManager, accounts.bob);
+            let result = contract.revoke_role(Role::WhitelistManager, accounts.bob);
+
+            set_sender(accounts.bob);
+            let whitelist_result = contract.add_token_to_whitelist(accounts.charlie);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(whitelist_result, Err(TemporaError::Unauthorized));
+
+            let emitted_events = get_emitted_events();
+
+            assert!(matches!(
+                &emitted_events[1],
+                Event::RoleRevoked(event)
+                    if event.role == Role::WhitelistManager
+                        && event.account == accounts.bob
+                        && event.revoked_by == accounts.alice
+            ));
+        }
+
+        #[ink::test]
+        fn revoke_admin_super_admin_role_fails() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.grant_role(Role::SuperAdmin, accounts.bob);
+
+            set_sender(accounts.bob);
+            let result = contract.revoke_role(Role::SuperAdmin, accounts.alice);
+
+            assert_eq!(result, Err(TemporaError::CannotRevokeAdmin));
+        }
+
+        #[ink::test]
+        fn renounce_role_works() {
+            let (mut contract, accounts) = init();
+
+            let result = contract.renounce_role(Role::Pauser);
+            let renounce_again_result = contract.renounce_role(Role::Pauser);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(renounce_again_result, Err(TemporaError::RoleNotGranted));
+            assert!(!contract.has_role(Role::Pauser, accounts.alice));
+        }
+
+        #[ink::test]
+        fn accept_admin_moves_super_admin_role() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.propose_admin(accounts.bob);
+
+            set_sender(accounts.bob);
+            let _ = contract.accept_admin();
+
+            assert!(contract.has_role(Role::SuperAdmin, accounts.bob));
+            assert!(!contract.has_role(Role::SuperAdmin, accounts.alice));
+            assert!(contract.has_role(Role::WhitelistManager, accounts.alice));
+        }
+
+        #[ink::test]
+        fn pause_works() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.grant_role(Role::Pauser, accounts.bob);
+
+            set_sender(accounts.bob);
+            let pause_result = contract.pause();
+            let is_paused = contract.is_paused();
+            let unpause_result = contract.unpause();
+
+            assert_eq!(pause_result, Ok(()));
+            assert!(is_paused);
+            assert_eq!(unpause_result, Ok(()));
+            assert!(!contract.is_paused());
+        }
+
+        #[ink::test]
+        fn pause_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.pause();
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+            assert!(!contract.is_paused());
+        }
+
+        #[ink::test]
+        fn pause_twice_fails() {
+            let (mut contract, _) = init();
+
+            let _ = contract.pause();
+            let result = contract.pause();
+
+            assert_eq!(result, Err(TemporaError::ContractPaused));
+        }
+
+        #[ink::test]
+        fn unpause_not_paused_contract_fails() {
+            let (mut contract, _) = init();
+
+            let result = contract.unpause();
+
+            assert_eq!(result, Err(TemporaError::ContractNotPaused));
+        }
+
+        #[ink::test]
+        fn paused_contract_blocks_schedule_changes_and_payments() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.pause();
+
+            let save_result = contract.save_schedule(
+                Hash::from([0x4; 32]),
+                String::from("task_456"),
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let schedule = contract.schedules.get(schedule_id).unwrap();
+            let update_result = contract.update_schedule(ScheduleConfiguration {
+                amount: 2 * amount,
+                ..schedule
+            });
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(amount);
+            let trigger_result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(save_result, Err(TemporaError::ContractPaused));
+            assert_eq!(update_result, Err(TemporaError::ContractPaused));
+            assert_eq!(trigger_result, Err(TemporaError::ContractPaused));
+            assert!(contract.payment_execution_counts.get(schedule_id).is_none());
+        }
+
+        #[ink::test]
+        fn paused_contract_allows_withdraw_and_remove_schedule() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            let _ = contract.pause();
+
+            let sender_initial_balance = get_balance(accounts.alice);
+
+            set_value_to_transfer(0);
+            let withdraw_result = contract.withdraw(schedule_id, amount);
+            let remove_result = contract.remove_schedule(schedule_id);
+
+            assert_eq!(withdraw_result, Ok(()));
+            assert_eq!(remove_result, Ok(()));
+            assert_eq!(
+                get_balance(accounts.alice),
+                sender_initial_balance + 2 * amount
+            );
+            assert_eq!(contract.get_escrow_balance(schedule_id), 0);
+        }
+
+        #[ink::test]
+        fn baseline_storage_migrates() {
+            let accounts = get_default_accounts();
+
+            set_callee::<DefaultEnvironment>(AccountId::from([0xC; 32]));
+
+            // Root of the first deployed version, which only held the admin and whitelist
+            let root_key = <TemporaContract as StorageKey>::KEY;
+            ink::env::set_contract_storage(&root_key, &(accounts.alice, vec![accounts.charlie]));
+
+            let mut contract: TemporaContract =
+                ink::env::get_contract_storage(&root_key).unwrap().unwrap();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let disabled_schedule_id = Hash::from([0x4; 32]);
+            let amount = 1000000;
+
+            for (id, enabled) in [(schedule_id, true), (disabled_schedule_id, false)] {
+                let legacy_schedule = LegacyScheduleConfiguration {
+                    id,
+                    task_id: String::from("task_123"),
+                    sender: accounts.alice,
+                    recipient: accounts.bob,
+                    amount,
+                    token_address: None,
+                    start_time: Some(10000),
+                    interval: Some(3600),
+                    execution_times: None,
+                    enabled,
+                };
+
+                ink::env::set_contract_storage(&(contract.schedules.key(), id), &legacy_schedule);
+            }
+
+            ink::env::set_contract_storage(
+                &(contract.payment_executions.key(), schedule_id),
+                &vec![10000_u64, 10000 + 3600 + 60],
+            );
+            contract
+                .user_schedules
+                .insert(accounts.alice, &vec![schedule_id, disabled_schedule_id]);
+
+            let early_result = contract.migrate_legacy_schedules(vec![schedule_id]);
+            let migrate_result = contract.migrate();
+            let legacy_result =
+                contract.migrate_legacy_schedules(vec![schedule_id, disabled_schedule_id]);
+            let repeated_result = contract.migrate_legacy_schedules(vec![schedule_id]);
+
+            let user_schedules = contract.get_user_schedules();
+
+            assert_eq!(early_result, Err(TemporaError::UnsupportedStorageVersion));
+            assert_eq!(migrate_result, Ok(()));
+            assert_eq!(legacy_result, Ok(()));
+            assert_eq!(repeated_result, Ok(()));
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.admin, accounts.alice);
+            assert!(Role::ALL
+                .iter()
+                .all(|role| contract.has_role(*role, accounts.alice)));
+            assert_eq!(contract.get_whitelisted_tokens(), vec![accounts.charlie]);
+            assert_eq!(
+                contract.get_token_info(accounts.charlie),
+                Some(TokenInfo::default())
+            );
+            assert_eq!(
+                contract.get_execution_tolerance(),
+                DEFAULT_EXECUTION_TOLERANCE
+            );
+            assert_eq!(user_schedules.len(), 2);
+            assert_eq!(
+                user_schedules[0].schedule_configuration.status,
+                ScheduleStatus::Active
+            );
+            assert_eq!(
+                user_schedules[1].schedule_configuration.status,
+                ScheduleStatus::Cancelled
+            );
+            assert_eq!(user_schedules[0].payment_executions.len(), 2);
+            assert_eq!(
+                user_schedules[0].payment_executions[1].timestamp,
+                10000 + 3600 + 60
+            );
+            assert_eq!(user_schedules[0].progress.executions, 2);
+            assert_eq!(user_schedules[0].progress.amount_paid, 2 * amount);
+            assert!(contract.is_slot_settled(schedule_id, 1));
+            assert_eq!(
+                ink::env::get_contract_storage::<_, Vec<Timestamp>>(&(
+                    contract.payment_executions.key(),
+                    schedule_id
+                )),
+                Ok(None)
+            );
+
+            // The slot paid by the first version cannot be paid again
+            set_timestamp_in_seconds(10000 + 3600 + 120);
+            set_value_to_transfer(amount);
+            let trigger_result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(trigger_result, Err(TemporaError::IncorrectExecutionTime));
+        }
+
+        #[ink::test]
+        fn add_token_to_whitelist_works() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.alice);
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            assert_eq!(contract.tokens_whitelist, vec![token_address]);
+        }
+
+        #[ink::test]
+        fn add_token_to_whitelist_by_common_user_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.add_token_to_whitelist(accounts.bob);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn add_token_to_whitelist_twice_fails() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let result = contract.add_token_to_whitelist(token_address);
+
+            assert_eq!(result, Err(TemporaError::TokenIsAlreadyWhiteslited));
+        }
+
+        #[ink::test]
+        fn remove_token_from_whitelist_works() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.remove_token_from_whitelist(token_address);
+
+            assert_eq!(contract.tokens_whitelist, Vec::new());
+            assert_eq!(contract.get_token_info(token_address), None);
+        }
+
+        #[ink::test]
+        fn remove_token_from_whitelist_by_common_user_fails() {
+            let (mut contract, accounts) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            set_sender(accounts.bob);
+            let result = contract.remove_token_from_whitelist(token_address);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn remove_nonexistent_token_from_whitelist_fails() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let result = contract.remove_token_from_whitelist(token_address);
+
+            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
+        }
+
+        #[ink::test]
+        fn remove_token_from_whitelist_flags_schedules() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let other_schedule_id = Hash::from([0x4; 32]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                other_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_token_from_whitelist(token_address);
+
+            let events = get_emitted_events();
+            let delisted_events = events
+                .iter()
+                .filter(|event| matches!(event, Event::ScheduleTokenDelisted(_)))
+                .count();
+
+            assert!(contract.is_schedule_delisted(schedule_id));
+            assert!(contract.is_schedule_delisted(other_schedule_id));
+            assert_eq!(delisted_events, 2);
+        }
+
+        #[ink::test]
+        fn trigger_delisted_schedule_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_token_from_whitelist(token_address);
+
+            set_timestamp_in_seconds(100);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
+        }
+
+        #[ink::test]
+        fn migrate_delisted_schedules_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let new_token_address = AccountId::from([0x8; 32]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_token_from_whitelist(token_address);
+            let _ = contract.add_token_to_whitelist(new_token_address);
+
+            let result = contract.migrate_delisted_schedules(token_address, new_token_address);
+
+            assert_eq!(result, Ok(vec![schedule_id]));
+            assert!(!contract.is_schedule_delisted(schedule_id));
+            assert_eq!(
+                contract.schedules.get(schedule_id).unwrap().token_address,
+                Some(new_token_address)
+            );
+            assert_eq!(
+                contract.get_token_schedules(new_token_address, 0, MAX_PAGE_SIZE),
+                vec![schedule_id]
+            );
+        }
+
+        #[ink::test]
+        fn migrate_delisted_schedules_to_nonwhitelisted_token_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_token_from_whitelist(token_address);
+
+            let result =
+                contract.migrate_delisted_schedules(token_address, AccountId::from([0x8; 32]));
+
+            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
+            assert!(contract.is_schedule_delisted(schedule_id));
+        }
+
+        #[ink::test]
+        fn migrate_delisted_schedules_of_other_sender_is_noop() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let new_token_address = AccountId::from([0x8; 32]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_token_from_whitelist(token_address);
+            let _ = contract.add_token_to_whitelist(new_token_address);
+
+            set_sender(accounts.bob);
+            let result = contract.migrate_delisted_schedules(token_address, new_token_address);
+
+            assert_eq!(result, Ok(Vec::new()));
+            assert!(contract.is_schedule_delisted(schedule_id));
+        }
+
+        #[ink::test]
+        fn set_token_info_works() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+            let token_info = TokenInfo {
+                symbol: Some(String::from("USDT")),
+                decimals: 6,
+                min_amount: 100,
+                max_amount: Some(1000),
+                enabled: true,
+            };
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let result = contract.set_token_info(token_address, token_info.clone());
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.get_token_info(token_address), Some(token_info));
+        }
+
+        #[ink::test]
+        fn set_token_info_by_common_user_fails() {
+            let (mut contract, accounts) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            set_sender(accounts.bob);
+            let result = contract.set_token_info(token_address, TokenInfo::default());
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn set_token_info_with_invalid_limits_fails() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let result = contract.set_token_info(
+                token_address,
+                TokenInfo {
+                    min_amount: 1000,
+                    max_amount: Some(100),
+                    ..TokenInfo::default()
+                },
+            );
+
+            assert_eq!(result, Err(TemporaError::InvalidTokenLimits));
+        }
+
+        #[ink::test]
+        fn set_info_of_nonwhitelisted_token_fails() {
+            let (mut contract, _) = init();
+
+            let result = contract.set_token_info(AccountId::from([0x9; 32]), TokenInfo::default());
+
+            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
+        }
+
+        #[ink::test]
+        fn save_schedule_outside_token_limits_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.set_token_info(
+                token_address,
+                TokenInfo {
+                    min_amount: 100,
+                    max_amount: Some(1000),
+                    ..TokenInfo::default()
+                },
+            );
+
+            let below_min_result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                99,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let above_max_result = contract.save_schedule(
+                schedule_id,
+                String::from("task_456"),
+                recipient,
+                1001,
+                Some(token_address),
+                start_time,
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(
+                below_min_result,
+                Err(TemporaError::AmountOutsideTokenLimits)
+            );
+            assert_eq!(
+                above_max_result,
+                Err(TemporaError::AmountOutsideTokenLimits)
+            );
+        }
+
+        #[ink::test]
+        fn save_schedule_with_disabled_token_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.set_token_info(
+                token_address,
+                TokenInfo {
+                    enabled: false,
+                    ..TokenInfo::default()
+                },
+            );
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                1000000,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
+        }
+
+        #[ink::test]
+        fn trigger_and_claim_in_disabled_token_fail() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                accounts.bob,
+                1000000,
+                Some(token_address),
+                Some(100),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Accumulate,
+            );
+            let _ = contract.set_token_info(
+                token_address,
+                TokenInfo {
+                    enabled: false,
+                    ..TokenInfo::default()
+                },
+            );
+
+            set_timestamp_in_seconds(100);
+            let trigger_result = contract.trigger_payment(schedule_id);
+
+            set_sender(accounts.bob);
+            let claim_result = contract.claim_accrued(schedule_id);
+
+            assert_eq!(trigger_result, Err(TemporaError::TokenIsNotWhitelisted));
+            assert_eq!(claim_result, Err(TemporaError::TokenIsNotWhitelisted));
+        }
+
+        #[ink::test]
+        fn get_tokens_paginates() {
+            let (mut contract, _) = init();
+
+            let tokens: Vec<AccountId> = (1..=3).map(|byte| AccountId::from([byte; 32])).collect();
+
+            for token_address in tokens.iter() {
+                let _ = contract.add_token_to_whitelist(*token_address);
+            }
+
+            assert_eq!(
+                contract.get_tokens(1, 1),
+                vec![(tokens[1], TokenInfo::default())]
+            );
+            assert_eq!(contract.get_tokens(0, MAX_PAGE_SIZE).len(), 3);
+        }
+
+        #[ink::test]
+        fn get_whitelisted_tokens_works() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            assert_eq!(contract.get_whitelisted_tokens(), vec![token_address]);
+        }
+
+        #[ink::test]
+        fn freeze_token_works() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let freeze_result = contract.freeze_token(token_address);
+            let is_frozen = contract.is_token_frozen(token_address);
+            let unfreeze_result = contract.unfreeze_token(token_address);
+
+            assert_eq!(freeze_result, Ok(()));
+            assert!(is_frozen);
+            assert_eq!(unfreeze_result, Ok(()));
+            assert!(!contract.is_token_frozen(token_address));
+        }
+
+        #[ink::test]
+        fn freeze_token_by_common_user_fails() {
+            let (mut contract, accounts) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            set_sender(accounts.bob);
+            let result = contract.freeze_token(token_address);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn freeze_token_twice_fails() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.freeze_token(token_address);
+            let result = contract.freeze_token(token_address);
+
+            assert_eq!(result, Err(TemporaError::TokenIsFrozen));
+        }
+
+        #[ink::test]
+        fn unfreeze_not_frozen_token_fails() {
+            let (mut contract, _) = init();
+
+            let result = contract.unfreeze_token(AccountId::from([0x9; 32]));
+
+            assert_eq!(result, Err(TemporaError::TokenIsNotFrozen));
+        }
+
+        #[ink::test]
+        fn save_schedule_with_frozen_token_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.freeze_token(token_address);
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::TokenIsFrozen));
+        }
+
+        #[ink::test]
+        fn trigger_payment_with_frozen_token_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.freeze_token(token_address);
+
+            set_timestamp_in_seconds(100);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::TokenIsFrozen));
+        }
+
+        #[ink::test]
+        fn get_token_schedules_lists_open_schedules() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let other_schedule_id = Hash::from([0x4; 32]);
+            let native_schedule_id = Hash::from([0x5; 32]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                other_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                native_schedule_id,
+                String::from("task_789"),
+                recipient,
+                amount,
+                None,
+                start_time,
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_schedule(other_schedule_id);
+
+            assert_eq!(
+                contract.get_token_schedules(token_address, 0, MAX_PAGE_SIZE),
+                vec![schedule_id]
+            );
+        }
+
+        #[ink::test]
+        fn remove_token_from_whitelist_flags_schedules_in_batches() {
+            let (mut contract, accounts) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+            let schedule_ids: Vec<Hash> = (0..=MAX_SCHEDULES_PER_BATCH as u8)
+                .map(|index| Hash::from([index; 32]))
+                .collect();
+
+            let _ = contract.add_token_to_whitelist(token_address);
+
+            for schedule_id in schedule_ids.iter() {
+                let _ = contract.save_schedule(
+                    *schedule_id,
+                    String::from("task_123"),
+                    accounts.bob,
+                    1000000,
+                    Some(token_address),
+                    Some(100),
+                    Some(100),
+                    None,
+                    None,
+                    Vec::new(),
+                    EndConditions::default(),
+                    MissedExecutionPolicy::Skip,
+                );
+            }
+
+            let whitelisted_result = contract.flag_delisted_schedules(token_address);
+            let remove_result = contract.remove_token_from_whitelist(token_address);
+            let unflagged_schedules = contract.get_token_schedules(token_address, 0, MAX_PAGE_SIZE);
+            let flag_result = contract.flag_delisted_schedules(token_address);
+
+            assert_eq!(
+                whitelisted_result,
+                Err(TemporaError::TokenIsAlreadyWhiteslited)
+            );
+            assert_eq!(remove_result, Ok(()));
+            assert_eq!(unflagged_schedules, vec![schedule_ids[0]]);
+            assert_eq!(flag_result, Ok(0));
+            assert!(schedule_ids
+                .iter()
+                .all(|schedule_id| contract.is_schedule_delisted(*schedule_id)));
+            assert!(contract
+                .get_token_schedules(token_address, 0, MAX_PAGE_SIZE)
+                .is_empty());
+        }
+
+        #[ink::test]
+        fn update_schedule_token_moves_token_schedules() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let other_token_address = AccountId::from([0x8; 32]);
+
+            let _ = contract.add_token_to_whitelist(other_token_address);
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                Some(token_address),
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let schedule = contract.schedules.get(schedule_id).unwrap();
+            let _ = contract.update_schedule(ScheduleConfiguration {
+                token_address: Some(other_token_address),
+                ..schedule
+            });
+
+            assert!(contract
+                .get_token_schedules(token_address, 0, MAX_PAGE_SIZE)
+                .is_empty());
+            assert_eq!(
+                contract.get_token_schedules(other_token_address, 0, MAX_PAGE_SIZE),
+                vec![schedule_id]
+            );
+        }
+
+        #[ink::test]
+        fn save_schedules_works() {
+            let (mut contract, accounts) = init();
+
+            let new_schedules = vec![
+                NewSchedule {
+                    id: Hash::from([0x3; 32]),
+                    task_id: String::from("task_123"),
+                    recipient: accounts.bob,
+                    amount: 1000000,
+                    token_address: None,
+                    start_time: Some(100),
+                    interval: Some(100),
+                    execution_times: None,
+                    recurrence: None,
+                    splits: Vec::new(),
+                    end_conditions: EndConditions::default(),
+                    missed_execution_policy: MissedExecutionPolicy::Skip,
+                },
+                NewSchedule {
+                    id: Hash::from([0x4; 32]),
+                    task_id: String::from("task_456"),
+                    recipient: accounts.bob,
+                    amount: 1000000,
+                    token_address: None,
+                    start_time: Some(100),
+                    interval: Some(100),
+                    execution_times: None,
+                    recurrence: None,
+                    splits: Vec::new(),
+                    end_conditions: EndConditions::default(),
+                    missed_execution_policy: MissedExecutionPolicy::Skip,
+                },
+            ];
+
+            let result = contract.save_schedules(new_schedules);
+
+            let user_schedules = contract.get_user_schedules();
+
+            assert_eq!(
+                result,
+                Ok(vec![Hash::from([0x3; 32]), Hash::from([0x4; 32])])
+            );
+            assert_eq!(user_schedules.len(), 2);
+            assert!(user_schedules
+                .iter()
+                .all(|data| data.schedule_configuration.sender == accounts.alice));
+        }
+
+        #[ink::test]
+        fn save_schedules_with_invalid_entry_fails() {
+            let (mut contract, accounts) = init();
+
+            let invalid_schedule = NewSchedule {
+                amount: 0,
+                ..NewSchedule {
+                    id: Hash::from([0x4; 32]),
+                    task_id: String::from("task_456"),
+                    recipient: accounts.bob,
+                    amount: 1000000,
+                    token_address: None,
+                    start_time: Some(100),
+                    interval: Some(100),
+                    execution_times: None,
+                    recurrence: None,
+                    splits: Vec::new(),
+                    end_conditions: EndConditions::default(),
+                    missed_execution_policy: MissedExecutionPolicy::Skip,
+                }
+            };
+            let new_schedules = vec![
+                NewSchedule {
+                    id: Hash::from([0x3; 32]),
+                    task_id: String::from("task_123"),
+                    recipient: accounts.bob,
+                    amount: 1000000,
+                    token_address: None,
+                    start_time: Some(100),
+                    interval: Some(100),
+                    execution_times: None,
+                    recurrence: None,
+                    splits: Vec::new(),
+                    end_conditions: EndConditions::default(),
+                    missed_execution_policy: MissedExecutionPolicy::Skip,
+                },
+                invalid_schedule,
+            ];
+
+            let result = contract.save_schedules(new_schedules);
+
+            assert_eq!(result, Err(TemporaError::ScheduleAmountCannotBeZero));
+            assert!(contract.get_user_schedules().is_empty());
+        }
+
+        #[ink::test]
+        fn save_schedules_with_duplicate_ids_fails() {
+            let (mut contract, accounts) = init();
+
+            let new_schedules = vec![
+                NewSchedule {
+                    id: Hash::from([0x3; 32]),
+                    task_id: String::from("task_123"),
+                    recipient: accounts.bob,
+                    amount: 1000000,
+                    token_address: None,
+                    start_time: Some(100),
+                    interval: Some(100),
+                    execution_times: None,
+                    recurrence: None,
+                    splits: Vec::new(),
+                    end_conditions: EndConditions::default(),
+                    missed_execution_policy: MissedExecutionPolicy::Skip,
+                },
+                NewSchedule {
+                    id: Hash::from([0x3; 32]),
+                    task_id: String::from("task_456"),
+                    recipient: accounts.bob,
+                    amount: 1000000,
+                    token_address: None,
+                    start_time: Some(100),
+                    interval: Some(100),
+                    execution_times: None,
+                    recurrence: None,
+                    splits: Vec::new(),
+                    end_conditions: EndConditions::default(),
+                    missed_execution_policy: MissedExecutionPolicy::Skip,
+                },
+            ];
+
+            let result = contract.save_schedules(new_schedules);
+
+            assert_eq!(
+                result,
+                Err(TemporaError::ScheduleConfigurationAlreadyExists)
+            );
+            assert!(contract.get_user_schedules().is_empty());
+        }
+
+        #[ink::test]
+        fn save_fixed_payment_schedule_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![100, 200]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let owner_schedules = contract.get_user_schedules();
+            set_sender(accounts.bob);
+            let recipient_schedules = contract.get_user_schedules();
+            let schedule_result = contract.schedules.get(schedule_id);
+
+            assert_eq!(owner_schedules.len(), 1);
+            assert_eq!(owner_schedules[0].schedule_configuration.id, schedule_id);
+            assert_eq!(recipient_schedules.len(), 1);
+            assert_eq!(
+                recipient_schedules[0].schedule_configuration.id,
+                schedule_id
+            );
+            assert!(schedule_result.is_some());
+        }
+
+        #[ink::test]
+        fn save_recurring_payment_schedule_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let owner_schedules = contract.get_user_schedules();
+            set_sender(accounts.bob);
+            let recipient_schedules = contract.get_user_schedules();
+            let schedule_result = contract.schedules.get(schedule_id);
+
+            assert_eq!(owner_schedules.len(), 1);
+            assert_eq!(owner_schedules[0].schedule_configuration.id, schedule_id);
+            assert_eq!(recipient_schedules.len(), 1);
+            assert_eq!(
+                recipient_schedules[0].schedule_configuration.id,
+                schedule_id
+            );
+            assert!(schedule_result.is_some());
+        }
+
+        #[ink::test]
+        fn save_repeated_fixed_payment_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![100, 200]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id.clone(),
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times.clone(),
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(
+                result,
+                Err(TemporaError::ScheduleConfigurationAlreadyExists)
+            );
+        }
+
+        #[ink::test]
+        fn save_fixed_payment_with_same_caller_and_recipient_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.alice;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![100, 200]);
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::CallerCannotBeRecipient));
+        }
+
+        #[ink::test]
+        fn save_fixed_payment_without_amount_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 0;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![100, 200]);
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::ScheduleAmountCannotBeZero));
+        }
+
+        #[ink::test]
+        fn save_fixed_payment_with_nonwhitelisted_token_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = Some(AccountId::from([0x9; 32]));
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![100, 200]);
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
+        }
+
+        #[ink::test]
+        fn save_schedule_without_timestamp_configuration_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = None;
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn remove_schedule_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_schedule(schedule_id);
+
+            let schedule_result = contract.schedules.get(schedule_id);
+
+            assert!(schedule_result.is_some());
+            assert_eq!(schedule_result.unwrap().status, ScheduleStatus::Cancelled);
+        }
+
+        #[ink::test]
+        fn remove_nonexistent_schedule_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_sender(accounts.bob);
+            let result = contract.remove_schedule(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
+        }
+
+        #[ink::test]
+        fn remove_schedule_by_nonowner_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+
+            set_sender(accounts.bob);
+            let result = contract.remove_schedule(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
+        }
+
+        #[ink::test]
+        fn remove_cancelled_schedule_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_schedule(schedule_id);
+            let result = contract.remove_schedule(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::InvalidScheduleStatusTransition));
+        }
+
+        #[ink::test]
+        fn pause_and_resume_schedule_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let pause_result = contract.pause_schedule(schedule_id);
+            let paused_status = contract.schedules.get(schedule_id).unwrap().status;
+
+            let resume_result = contract.resume_schedule(schedule_id);
+            let resumed_status = contract.schedules.get(schedule_id).unwrap().status;
+
+            assert_eq!(pause_result, Ok(()));
+            assert_eq!(paused_status, ScheduleStatus::Paused);
+            assert_eq!(resume_result, Ok(()));
+            assert_eq!(resumed_status, ScheduleStatus::Active);
+
+            let emitted_events = get_emitted_events();
+
+            assert!(emitted_events.iter().any(|event| matches!(
+                event,
+                Event::ScheduleStatusChanged(event)
+                    if event.previous_status == ScheduleStatus::Active
+                        && event.status == ScheduleStatus::Paused
+            )));
+        }
+
+        #[ink::test]
+        fn resume_active_schedule_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let result = contract.resume_schedule(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::InvalidScheduleStatusTransition));
+        }
+
+        #[ink::test]
+        fn pause_schedule_by_nonowner_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_sender(accounts.bob);
+            let result = contract.pause_schedule(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
+        }
+
+        #[ink::test]
+        fn trigger_paused_schedule_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.pause_schedule(schedule_id);
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::ScheduleConfigurationDisabled));
+        }
+
+        #[ink::test]
+        fn trigger_last_fixed_payment_completes_schedule() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![10000, 20000]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let status_after_first_slot = contract.schedules.get(schedule_id).unwrap().status;
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(20000);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let status_after_last_slot = contract.schedules.get(schedule_id).unwrap().status;
+
+            assert_eq!(status_after_first_slot, ScheduleStatus::Active);
+            assert_eq!(status_after_last_slot, ScheduleStatus::Completed);
+        }
+
+        #[ink::test]
+        fn get_user_schedules_reports_expired_schedule() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![10000, 20000]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(20000 + DEFAULT_EXECUTION_TOLERANCE + 1);
+            let user_schedules = contract.get_user_schedules();
+
+            assert_eq!(
+                user_schedules[0].schedule_configuration.status,
+                ScheduleStatus::Expired
+            );
+            assert_eq!(
+                contract.schedules.get(schedule_id).unwrap().status,
+                ScheduleStatus::Active
+            );
+        }
+
+        #[ink::test]
+        fn update_schedule_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let mut schedule = contract.schedules.get(schedule_id).unwrap();
+            schedule.amount = 2000000;
+
+            let _ = contract.update_schedule(schedule);
+
+            let updated_schedule = contract.schedules.get(schedule_id).unwrap();
+
+            assert_eq!(updated_schedule.amount, 2000000);
+        }
+
+        #[ink::test]
+        fn update_nonexistent_schedule_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+
+            let schedule = ScheduleConfiguration {
+                id: schedule_id,
+                task_id: String::from("task_123"),
+                sender: accounts.bob,
+                recipient: accounts.bob,
+                amount: 1000000,
+                token_address: None,
+                start_time: Some(100),
+                interval: Some(100),
+                execution_times: None,
+                recurrence: None,
+                stream: None,
+                splits: Vec::new(),
+                end_conditions: EndConditions::default(),
+                missed_execution_policy: MissedExecutionPolicy::default(),
+                status: ScheduleStatus::Active,
+            };
+
+            let result = contract.update_schedule(schedule);
+
+            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
+        }
+
+        #[ink::test]
+        fn trigger_native_payment_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(amount);
+
+            let result = contract.trigger_payment(schedule_id);
+
+            let user_schedules = contract.get_user_schedules();
+            let payment_executions = contract.get_payment_executions(schedule_id, 0, MAX_PAGE_SIZE);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(user_schedules[0].payment_executions.len(), 1);
+            assert_eq!(payment_executions.len(), 1);
+            assert_eq!(get_balance(recipient), recipient_initial_balance + amount);
+        }
+
+        #[ink::test]
+        fn trigger_nonexistent_schedule_fails() {
+            let (mut contract, _) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::ScheduleConfigurationNotFound));
+        }
+
+        #[ink::test]
+        fn trigger_payment_by_keeper_with_value_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 1_000_000_000);
+
+            set_sender(accounts.charlie);
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+            assert!(contract.payment_execution_counts.get(schedule_id).is_none());
+        }
+
+        #[ink::test]
+        fn trigger_payment_by_keeper_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_sender(accounts.charlie);
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            let receipt = contract.get_payment_receipt(0).unwrap();
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(recipient), recipient_initial_balance + amount);
+            assert_eq!(receipt.executor, accounts.charlie);
+        }
+
+        #[ink::test]
+        fn get_due_schedules_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let later_schedule_id = Hash::from([0x4; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let interval = Some(DUE_BUCKET_DURATION);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(5000),
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                later_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                None,
+                Some(4000),
+                None,
+                Some(vec![4000, 20000]),
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert!(contract.get_due_schedules(3999, MAX_PAGE_SIZE).is_empty());
+            assert_eq!(
+                contract.get_due_schedules(5000, MAX_PAGE_SIZE),
+                vec![later_schedule_id, schedule_id]
+            );
+            assert_eq!(contract.get_due_schedules(5000, 1), vec![later_schedule_id]);
+        }
+
+        #[ink::test]
+        fn due_index_skips_missed_slots() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                accounts.bob,
+                1000000,
+                None,
+                Some(100),
+                Some(3600),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(2500);
+
+            assert!(contract.get_due_schedules(2500, MAX_PAGE_SIZE).is_empty());
+            assert_eq!(
+                contract.get_due_schedules(3700, MAX_PAGE_SIZE),
+                vec![schedule_id]
+            );
+        }
+
+        #[ink::test]
+        fn trigger_payment_moves_schedule_in_due_index() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let later_schedule_id = Hash::from([0x4; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let interval = Some(DUE_BUCKET_DURATION);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(5000),
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                later_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                None,
+                Some(4000),
+                None,
+                Some(vec![4000, 20000]),
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(4000);
+            set_value_to_transfer(amount);
+            let _ = contract.trigger_payment(later_schedule_id);
+
+            assert_eq!(
+                contract.get_due_schedules(5000, MAX_PAGE_SIZE),
+                vec![schedule_id]
+            );
+            assert_eq!(
+                contract.get_due_schedules(20000, MAX_PAGE_SIZE),
+                vec![schedule_id, later_schedule_id]
+            );
+        }
+
+        #[ink::test]
+        fn disabled_schedules_leave_due_index() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let later_schedule_id = Hash::from([0x4; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let interval = Some(DUE_BUCKET_DURATION);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(5000),
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                later_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                None,
+                Some(4000),
+                None,
+                Some(vec![4000, 20000]),
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.pause_schedule(schedule_id);
+            let _ = contract.remove_schedule(later_schedule_id);
+
+            let due_while_paused = contract.get_due_schedules(20000, MAX_PAGE_SIZE);
+
+            let _ = contract.resume_schedule(schedule_id);
+
+            assert!(due_while_paused.is_empty());
+            assert_eq!(
+                contract.get_due_schedules(20000, MAX_PAGE_SIZE),
+                vec![schedule_id]
+            );
+        }
+
+        #[ink::test]
+        fn completed_schedule_leaves_due_index() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let later_schedule_id = Hash::from([0x4; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let interval = Some(DUE_BUCKET_DURATION);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(5000),
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                later_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                None,
+                Some(4000),
+                None,
+                Some(vec![4000, 20000]),
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(4000);
+            set_value_to_transfer(amount);
+            let _ = contract.trigger_payment(later_schedule_id);
+
+            set_timestamp_in_seconds(20000);
+            set_value_to_transfer(amount);
+            let _ = contract.trigger_payment(later_schedule_id);
+
+            assert_eq!(
+                contract.schedules.get(later_schedule_id).unwrap().status,
+                ScheduleStatus::Completed
+            );
+            assert_eq!(
+                contract.get_due_schedules(u64::MAX, MAX_PAGE_SIZE),
+                vec![schedule_id]
+            );
+        }
+
+        #[ink::test]
+        fn trigger_many_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let later_schedule_id = Hash::from([0x4; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                later_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                None,
+                Some(10000),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+            let _ = contract.deposit(later_schedule_id, 2 * amount);
+
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_many(vec![schedule_id, later_schedule_id]);
+
+            assert_eq!(
+                result,
+                Ok(vec![
+                    Ok(PaymentOutcome::Success),
+                    Err(TemporaError::IncorrectExecutionTime)
+                ])
+            );
+            assert_eq!(get_balance(recipient), recipient_initial_balance + amount);
+            assert_eq!(contract.get_escrow_balance(schedule_id), amount);
+            assert_eq!(contract.get_escrow_balance(later_schedule_id), 2 * amount);
+        }
+
+        #[ink::test]
+        fn trigger_many_refunds_unused_value() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let later_schedule_id = Hash::from([0x4; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                later_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                None,
+                Some(10000),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let sender_initial_balance = get_balance(accounts.alice);
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(3 * amount);
+            let result = contract.trigger_many(vec![later_schedule_id, schedule_id]);
+
+            assert_eq!(
+                result,
+                Ok(vec![
+                    Err(TemporaError::IncorrectExecutionTime),
+                    Ok(PaymentOutcome::Success)
+                ])
+            );
+            assert_eq!(get_balance(recipient), recipient_initial_balance + amount);
+            assert_eq!(get_balance(accounts.alice), sender_initial_balance - amount);
+            assert_eq!(get_balance(AccountId::from([0xC; 32])), 0);
+        }
+
+        #[ink::test]
+        fn trigger_many_without_enough_value_fails_per_schedule() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let later_schedule_id = Hash::from([0x4; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                later_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                None,
+                Some(10000),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(10000);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_many(vec![schedule_id, later_schedule_id]);
+
+            assert_eq!(
+                result,
+                Ok(vec![
+                    Ok(PaymentOutcome::Success),
+                    Err(TemporaError::InsufficientBalance)
+                ])
+            );
+            assert!(contract
+                .payment_execution_counts
+                .get(later_schedule_id)
+                .is_none());
+        }
+
+        #[ink::test]
+        fn trigger_many_reports_uncovered_split_payment_per_schedule() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let split_schedule_id = Hash::from([0x4; 32]);
+            let amount = 1000000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                accounts.bob,
+                amount,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let _ = contract.save_schedule(
+                split_schedule_id,
+                String::from("task_456"),
+                accounts.bob,
+                3 * amount,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                vec![PaymentSplit {
+                    recipient: accounts.charlie,
+                    share: SplitShare::Amount(amount),
+                }],
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            let _ = contract.deposit(schedule_id, amount);
+            set_value_to_transfer(3 * amount);
+            let _ = contract.deposit(split_schedule_id, 3 * amount);
+
+            // The contract no longer holds what the split schedule's escrow records
+            set_account_balance::<DefaultEnvironment>(AccountId::from([0xC; 32]), 2 * amount);
+
+            let bob_initial_balance = get_balance(accounts.bob);
+            let charlie_initial_balance = get_balance(accounts.charlie);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_many(vec![split_schedule_id, schedule_id]);
+
+            assert_eq!(
+                result,
+                Ok(vec![
+                    Ok(PaymentOutcome::Failed(TemporaError::TransferError)),
+                    Ok(PaymentOutcome::Success)
+                ])
+            );
+            assert_eq!(get_balance(accounts.bob), bob_initial_balance + amount);
+            assert_eq!(get_balance(accounts.charlie), charlie_initial_balance);
+            assert_eq!(contract.get_escrow_balance(split_schedule_id), 3 * amount);
+        }
+
+        #[ink::test]
+        fn trigger_many_above_batch_size_fails() {
+            let (mut contract, _) = init();
+
+            let schedule_ids = vec![Hash::from([0x3; 32]); MAX_SCHEDULES_PER_BATCH + 1];
+
+            let result = contract.trigger_many(schedule_ids);
+
+            assert_eq!(result, Err(TemporaError::BatchTooLarge));
+        }
+
+        #[ink::test]
+        fn set_keeper_tip_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let tip = 1000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let set_result = contract.set_keeper_tip(schedule_id, tip);
+            let tip_after_set = contract.get_keeper_tip(schedule_id);
+            let remove_result = contract.set_keeper_tip(schedule_id, 0);
+
+            assert_eq!(set_result, Ok(()));
+            assert_eq!(tip_after_set, tip);
+            assert_eq!(remove_result, Ok(()));
+            assert_eq!(contract.get_keeper_tip(schedule_id), 0);
+        }
+
+        #[ink::test]
+        fn set_keeper_tip_by_other_account_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let tip = 1000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_sender(accounts.charlie);
+            let result = contract.set_keeper_tip(schedule_id, tip);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn trigger_payment_by_keeper_pays_tip() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let tip = 1000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.set_keeper_tip(schedule_id, tip);
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            set_account_balance::<DefaultEnvironment>(accounts.charlie, 0);
+
+            set_sender(accounts.charlie);
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(accounts.charlie), tip);
+            assert_eq!(contract.get_escrow_balance(schedule_id), amount - tip);
+        }
+
+        #[ink::test]
+        fn trigger_payment_by_sender_pays_no_tip() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let tip = 1000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.set_keeper_tip(schedule_id, tip);
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.get_escrow_balance(schedule_id), amount);
+        }
+
+        #[ink::test]
+        fn get_due_schedules_with_tips_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let tip = 1000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let untipped_schedule_id = Hash::from([0x4; 32]);
+
+            let _ = contract.save_schedule(
+                untipped_schedule_id,
+                String::from("task_456"),
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.set_execution_tolerance(0);
+            let _ = contract.set_keeper_tip(schedule_id, tip);
+
+            set_timestamp_in_seconds(50);
+            let before_start = contract.get_due_schedules_with_tips(0, MAX_PAGE_SIZE);
+
+            set_timestamp_in_seconds(100);
+            let at_start = contract.get_due_schedules_with_tips(0, MAX_PAGE_SIZE);
+
+            assert!(before_start.is_empty());
+            assert_eq!(at_start, vec![(schedule_id, tip)]);
+        }
+
+        #[ink::test]
+        fn cancelled_schedule_loses_keeper_tip() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let other_schedule_id = Hash::from([0x4; 32]);
+            let tip = 1000;
+
+            for (id, task_id) in [(schedule_id, "task_123"), (other_schedule_id, "task_456")] {
+                let _ = contract.save_schedule(
+                    id,
+                    String::from(task_id),
+                    accounts.bob,
+                    1000000,
+                    None,
+                    Some(100),
+                    Some(100),
+                    None,
+                    None,
+                    Vec::new(),
+                    EndConditions::default(),
+                    MissedExecutionPolicy::Skip,
+                );
+                let _ = contract.set_keeper_tip(id, tip);
+            }
+
+            let _ = contract.remove_schedule(schedule_id);
+            let set_result = contract.set_keeper_tip(schedule_id, tip);
+
+            set_timestamp_in_seconds(100);
+
+            assert_eq!(set_result, Err(TemporaError::ScheduleConfigurationDisabled));
+            assert_eq!(contract.get_keeper_tip(schedule_id), 0);
+            assert_eq!(contract.tipped_schedule_count.get(), Some(1));
+            assert_eq!(
+                contract.get_due_schedules_with_tips(0, MAX_PAGE_SIZE),
+                vec![(other_schedule_id, tip)]
+            );
+        }
+
+        #[ink::test]
+        fn trigger_payment_by_keeper_before_execution_time_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            let _ = contract.set_execution_tolerance(0);
+
+            set_sender(accounts.charlie);
+            set_timestamp_in_seconds(50);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::IncorrectExecutionTime));
+            assert_eq!(contract.get_escrow_balance(schedule_id), 2 * amount);
+        }
+
+        #[ink::test]
+        fn trigger_disabled_schedule_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.remove_schedule(schedule_id);
+
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::ScheduleConfigurationDisabled));
+        }
+
+        #[ink::test]
+        fn trigger_native_payment_without_sufficient_balance_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount / 2);
+
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn update_schedule_sender_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let mut schedule = contract.schedules.get(schedule_id).unwrap();
+            schedule.sender = accounts.charlie;
+
+            let result = contract.update_schedule(schedule);
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+            assert_eq!(
+                contract.schedules.get(schedule_id).unwrap().sender,
+                accounts.alice
+            );
+        }
+
+        #[ink::test]
+        fn set_execution_tolerance_works() {
+            let (mut contract, _) = init();
+
+            let result = contract.set_execution_tolerance(60);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.get_execution_tolerance(), 60);
+        }
+
+        #[ink::test]
+        fn set_execution_tolerance_by_common_user_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.set_execution_tolerance(60);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+            assert_eq!(
+                contract.get_execution_tolerance(),
+                DEFAULT_EXECUTION_TOLERANCE
+            );
+        }
+
+        #[ink::test]
+        fn save_fixed_payment_with_unordered_execution_times_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![200, 100]);
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn trigger_payment_before_execution_time_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(10000 - DEFAULT_EXECUTION_TOLERANCE - 1);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::IncorrectExecutionTime));
+        }
+
+        #[ink::test]
+        fn trigger_recurring_payment_twice_in_same_slot_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(10000 + 3600 + 10);
+            set_value_to_transfer(amount);
+            let first_result = contract.trigger_payment(schedule_id);
+            let second_result = contract.trigger_payment(schedule_id);
+
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+            set_value_to_transfer(amount);
+            let next_slot_result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(first_result, Ok(()));
+            assert_eq!(second_result, Err(TemporaError::IncorrectExecutionTime));
+            assert_eq!(next_slot_result, Ok(()));
+            assert_eq!(contract.payment_execution_counts.get(schedule_id), Some(2));
+            assert_eq!(
+                contract.last_settled_slots.get(schedule_id),
+                Some(10000 + 2 * 3600)
+            );
+        }
+
+        #[ink::test]
+        fn trigger_catch_up_payment_pays_missed_slots() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::CatchUp,
+            );
+
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+            let due_amount = contract.get_due_amount(schedule_id);
+
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_value_to_transfer(3 * amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(due_amount, Ok(3 * amount));
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                get_balance(recipient),
+                recipient_initial_balance + 3 * amount
+            );
+            assert!(contract.is_slot_settled(schedule_id, 0));
+            assert!(contract.is_slot_settled(schedule_id, 1));
+            assert!(contract.is_slot_settled(schedule_id, 2));
+            assert_eq!(contract.get_last_settled_slot_index(schedule_id), Some(2));
+            assert_eq!(
+                contract
+                    .schedule_progress
+                    .get(schedule_id)
+                    .unwrap()
+                    .executions,
+                3
+            );
+        }
+
+        #[ink::test]
+        fn trigger_catch_up_payment_with_single_amount_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::CatchUp,
+            );
+
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn trigger_skip_payment_skips_missed_slots() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Ok(()));
+            assert!(!contract.is_slot_settled(schedule_id, 0));
+            assert!(!contract.is_slot_settled(schedule_id, 1));
+            assert!(contract.is_slot_settled(schedule_id, 2));
+        }
+
+        #[ink::test]
+        fn trigger_accumulate_payment_accrues_missed_slots() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Accumulate,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            let withdraw_result = contract.withdraw(schedule_id, amount);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                contract.get_accrued_amount(schedule_id, recipient),
+                2 * amount
+            );
+            assert!(contract.is_slot_settled(schedule_id, 0));
+            assert!(contract.is_slot_settled(schedule_id, 2));
+            assert_eq!(withdraw_result, Err(TemporaError::InsufficientBalance));
+
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_sender(recipient);
+            let claim_result = contract.claim_accrued(schedule_id);
+
+            assert_eq!(claim_result, Ok(2 * amount));
+            assert_eq!(
+                get_balance(recipient),
+                recipient_initial_balance + 2 * amount
+            );
+            assert_eq!(contract.get_accrued_amount(schedule_id, recipient), 0);
+            assert_eq!(contract.get_escrow_balance(schedule_id), 0);
+        }
+
+        #[ink::test]
+        fn trigger_accumulate_payment_with_only_missed_slots_and_value_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let amount = 1000000;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                accounts.bob,
+                amount,
+                None,
+                Some(10000),
+                Some(3600),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Accumulate,
+            );
+
+            set_timestamp_in_seconds(10000 + 2 * 3600 + 1000);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::UnexpectedTransferredValue));
+        }
+
+        #[ink::test]
+        fn trigger_psp22_payment_with_value_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let amount = 1000000;
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                accounts.bob,
+                amount,
+                Some(token_address),
+                Some(10000),
+                Some(3600),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(10000);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::UnexpectedTransferredValue));
+        }
+
+        #[ink::test]
+        fn claim_accrued_accrues_missed_slots() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Accumulate,
+            );
+
+            set_value_to_transfer(5 * amount);
+            let _ = contract.deposit(schedule_id, 5 * amount);
+
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+
+            set_sender(recipient);
+            let claim_result = contract.claim_accrued(schedule_id);
+
+            assert_eq!(claim_result, Ok(2 * amount));
+            assert_eq!(contract.get_escrow_balance(schedule_id), 3 * amount);
+            assert!(contract.is_slot_settled(schedule_id, 1));
+            assert!(!contract.is_slot_settled(schedule_id, 2));
+        }
+
+        #[ink::test]
+        fn claim_accrued_collects_protocol_fee() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let fee = amount / 10;
+
+            let _ = contract.set_protocol_fee(ProtocolFee {
+                basis_points: 1000,
+                minimum_fees: Vec::new(),
+            });
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                recipient,
+                amount,
+                None,
+                Some(10000),
+                Some(3600),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Accumulate,
+            );
+
+            set_value_to_transfer(5 * amount);
+            let _ = contract.deposit(schedule_id, 5 * amount);
+
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+
+            set_sender(recipient);
+            let claim_result = contract.claim_accrued(schedule_id);
+
+            assert_eq!(claim_result, Ok(2 * (amount - fee)));
+            assert_eq!(contract.get_collected_fees(None), 2 * fee);
+            assert_eq!(contract.get_escrow_balance(schedule_id), 3 * amount);
+            assert_eq!(contract.accrued_totals.get(schedule_id), Some(0));
+        }
+
+        #[ink::test]
+        fn trigger_fixed_payment_after_last_execution_time_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = Some(vec![10000, 20000]);
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+
+            set_timestamp_in_seconds(10000);
+            let first_slot_result = contract.trigger_payment(schedule_id);
+
+            set_value_to_transfer(amount);
+
+            set_timestamp_in_seconds(20000 + DEFAULT_EXECUTION_TOLERANCE + 1);
+            let expired_result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(first_slot_result, Ok(()));
+            assert_eq!(expired_result, Err(TemporaError::IncorrectExecutionTime));
+        }
+
+        #[ink::test]
+        fn save_schedule_with_zero_max_executions_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+            let end_conditions = EndConditions {
+                max_executions: Some(0),
+                ..Default::default()
+            };
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn save_schedule_with_end_time_before_start_time_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+            let end_conditions = EndConditions {
+                end_time: Some(9999),
+                ..Default::default()
+            };
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn save_schedule_with_total_amount_below_amount_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+            let end_conditions = EndConditions {
+                max_total_amount: Some(amount - 1),
+                ..Default::default()
+            };
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn trigger_recurring_payment_completes_after_max_executions() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+            let end_conditions = EndConditions {
+                max_executions: Some(2),
+                ..Default::default()
+            };
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000);
+            let first_result = contract.trigger_payment(schedule_id);
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000 + 3600);
+            let second_result = contract.trigger_payment(schedule_id);
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+            let third_result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(first_result, Ok(()));
+            assert_eq!(second_result, Ok(()));
+            assert_eq!(
+                third_result,
+                Err(TemporaError::ScheduleConfigurationDisabled)
+            );
+            assert_eq!(
+                contract.schedules.get(schedule_id).unwrap().status,
+                ScheduleStatus::Completed
+            );
+            assert_eq!(
+                contract.schedule_progress.get(schedule_id),
+                Some(ScheduleProgress {
+                    executions: 2,
+                    amount_paid: 2 * amount,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn trigger_recurring_payment_completes_before_exceeding_total_amount() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+            let end_conditions = EndConditions {
+                max_total_amount: Some(2 * amount + amount / 2),
+                ..Default::default()
+            };
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let status_after_first_payment = contract.schedules.get(schedule_id).unwrap().status;
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000 + 3600);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let status_after_second_payment = contract.schedules.get(schedule_id).unwrap().status;
+
+            assert_eq!(status_after_first_payment, ScheduleStatus::Active);
+            assert_eq!(status_after_second_payment, ScheduleStatus::Completed);
+        }
+
+        #[ink::test]
+        fn trigger_recurring_payment_after_end_time_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+            let end_conditions = EndConditions {
+                end_time: Some(10000 + 3600 + 1),
+                ..Default::default()
+            };
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000 + 2 * 3600);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::EndConditionReached));
+            assert_eq!(
+                contract.get_user_schedules()[0]
+                    .schedule_configuration
+                    .status,
+                ScheduleStatus::Expired
+            );
+        }
+
+        #[ink::test]
+        fn trigger_last_recurring_payment_before_end_time_completes_schedule() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+            let end_conditions = EndConditions {
+                end_time: Some(10000 + 3600 + 1),
+                ..Default::default()
+            };
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000 + 3600);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                contract.schedules.get(schedule_id).unwrap().status,
+                ScheduleStatus::Completed
+            );
+        }
+
+        #[ink::test]
+        fn update_schedule_with_reached_max_executions_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(10000);
+            let interval = Some(3600);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(10000);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let mut schedule = contract.schedules.get(schedule_id).unwrap();
+            schedule.end_conditions.max_executions = Some(1);
+
+            let result = contract.update_schedule(schedule);
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn save_recurrence_schedule_without_start_time_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = None;
+            let interval = None;
+            let execution_times = None;
+            let recurrence = Some(RecurrenceRule {
+                frequency: Frequency::Monthly,
+                interval: 1,
+                by_month_day: vec![1],
+                by_day: Vec::new(),
+                count: None,
+            });
+
+            let result = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                recurrence,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn save_recurrence_schedule_without_occurrences_fails() {
+            let (mut contract, accounts) = init();
+
+            // The 31st of a month is never its first Friday
+            let recurrence = Some(RecurrenceRule {
+                frequency: Frequency::Monthly,
+                interval: 1,
+                by_month_day: vec![31],
+                by_day: vec![WeekdayRule {
+                    weekday: Weekday::Friday,
+                    nth: Some(1),
+                }],
+                count: None,
+            });
+
+            let result = contract.save_schedule(
+                Hash::from([0x3; 32]),
+                String::from("task_123"),
+                accounts.bob,
+                1000000,
+                None,
+                Some(100),
+                None,
+                None,
+                recurrence,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+        }
+
+        #[ink::test]
+        fn trigger_monthly_recurrence_payment_works() {
+            let (mut contract, accounts) = init();
+
+            // 2024-01-01 09:00:00 UTC
+            let first_of_january = 1_704_099_600;
+            let first_of_february = first_of_january + 31 * 86_400;
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(first_of_january);
+            let interval = None;
+            let execution_times = None;
+            let recurrence = Some(RecurrenceRule {
+                frequency: Frequency::Monthly,
+                interval: 1,
+                by_month_day: vec![1],
+                by_day: Vec::new(),
+                count: None,
+            });
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                recurrence,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(first_of_january + 15 * 86_400);
+            let mid_month_result = contract.trigger_payment(schedule_id);
+
+            set_value_to_transfer(amount);
+            set_timestamp_in_seconds(first_of_february);
+            let first_of_month_result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(mid_month_result, Err(TemporaError::IncorrectExecutionTime));
+            assert_eq!(first_of_month_result, Ok(()));
+            assert_eq!(
+                contract.last_settled_slots.get(schedule_id),
+                Some(first_of_february)
+            );
+        }
+
+        #[ink::test]
+        fn get_next_occurrences_works() {
+            let (mut contract, accounts) = init();
+
+            // 2024-01-01 09:00:00 UTC
+            let first_of_january = 1_704_099_600;
+            let day = 86_400;
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(first_of_january);
+            let interval = None;
+            let execution_times = None;
+            let recurrence = Some(RecurrenceRule {
+                frequency: Frequency::Monthly,
+                interval: 1,
+                by_month_day: vec![-1],
+                by_day: Vec::new(),
+                count: None,
+            });
+            let end_conditions = EndConditions {
+                max_executions: Some(2),
+                ..Default::default()
+            };
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                recurrence,
+                Vec::new(),
+                end_conditions,
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_timestamp_in_seconds(first_of_january);
+            let result = contract.get_next_occurrences(schedule_id, 5);
+
+            assert_eq!(
+                result,
+                Ok(vec![
+                    first_of_january + 30 * day,
+                    first_of_january + (31 + 28) * day
+                ])
+            );
+        }
+
+        #[ink::test]
+        fn admin_handover_emits_events() {
+            let (mut contract, accounts) = init();
+
+            let _ = contract.propose_admin(accounts.bob);
+
+            set_sender(accounts.bob);
+            let _ = contract.accept_admin();
+
+            let events = get_emitted_events();
+
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                &events[0],
+                Event::AdminProposed(event)
+                    if event.current_admin == accounts.alice
+                        && event.proposed_admin == accounts.bob
+            ));
+            assert!(matches!(
+                &events[1],
+                Event::AdminChanged(event)
+                    if event.previous_admin == accounts.alice && event.new_admin == accounts.bob
+            ));
+        }
+
+        #[ink::test]
+        fn token_whitelist_changes_emit_events() {
+            let (mut contract, _) = init();
+
+            let token_address = AccountId::from([0x9; 32]);
+
+            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.remove_token_from_whitelist(token_address);
+
+            let events = get_emitted_events();
+
+            assert_eq!(events.len(), 2);
+            assert!(matches!(
+                &events[0],
+                Event::TokenWhitelisted(event) if event.token_address == token_address
+            ));
+            assert!(matches!(
+                &events[1],
+                Event::TokenDelisted(event) if event.token_address == token_address
+            ));
+        }
+
+        #[ink::test]
+        fn schedule_lifecycle_emits_events() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let mut schedule = contract.schedules.get(schedule_id).unwrap();
+            schedule.amount = 2000000;
+            let _ = contract.update_schedule(schedule);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(2000000);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let _ = contract.remove_schedule(schedule_id);
+
+            let events = get_emitted_events();
+
+            assert_eq!(events.len(), 4);
+            assert!(matches!(
+                &events[0],
+                Event::ScheduleCreated(event)
+                    if event.schedule_id == schedule_id
+                        && event.sender == accounts.alice
+                        && event.recipient == recipient
+            ));
+            assert!(matches!(
+                &events[1],
+                Event::ScheduleUpdated(event) if event.schedule_id == schedule_id
+            ));
+            assert!(matches!(
+                &events[2],
+                Event::PaymentExecuted(event)
+                    if event.schedule_id == schedule_id
+                        && event.amount == 2000000
+                        && event.slot == 100
+            ));
+            assert!(matches!(
+                &events[3],
+                Event::ScheduleStatusChanged(event)
+                    if event.schedule_id == schedule_id
+                        && event.status == ScheduleStatus::Cancelled
+            ));
+        }
+
+        #[ink::test]
+        fn deposit_native_to_escrow_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(3 * amount);
+            let result = contract.deposit(schedule_id, 3 * amount);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.get_escrow_balance(schedule_id), 3 * amount);
+            assert_eq!(contract.get_escrow_covered_executions(schedule_id), Ok(3));
+        }
+
+        #[ink::test]
+        fn deposit_with_wrong_transferred_value_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            let result = contract.deposit(schedule_id, 3 * amount);
+
+            assert_eq!(result, Err(TemporaError::InsufficientBalance));
+            assert_eq!(contract.get_escrow_balance(schedule_id), 0);
+        }
+
+        #[ink::test]
+        fn deposit_by_nonowner_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_sender(accounts.bob);
+            let result = contract.deposit(schedule_id, amount);
+
+            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
+        }
+
+        #[ink::test]
+        fn trigger_payment_from_escrow_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(recipient), recipient_initial_balance + amount);
+            assert_eq!(contract.get_escrow_balance(schedule_id), amount);
+            assert_eq!(contract.get_escrow_covered_executions(schedule_id), Ok(1));
+        }
+
+        #[ink::test]
+        fn set_protocol_fee_works() {
+            let (mut contract, _) = init();
+
+            let protocol_fee = ProtocolFee {
+                basis_points: 100,
+                minimum_fees: vec![(None, 5000)],
+            };
+
+            let result = contract.set_protocol_fee(protocol_fee.clone());
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.get_protocol_fee(), protocol_fee);
+            assert_eq!(contract.get_protocol_fee_version(), 1);
+        }
+
+        #[ink::test]
+        fn set_protocol_fee_fails() {
+            let (mut contract, accounts) = init();
+
+            set_sender(accounts.bob);
+            let result = contract.set_protocol_fee(ProtocolFee::default());
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn set_protocol_fee_with_invalid_basis_points_fails() {
+            let (mut contract, _) = init();
+
+            let result = contract.set_protocol_fee(ProtocolFee {
+                basis_points: BASIS_POINTS_DENOMINATOR + 1,
+                minimum_fees: Vec::new(),
+            });
+
+            assert_eq!(result, Err(TemporaError::InvalidProtocolFee));
+        }
+
+        #[ink::test]
+        fn trigger_payment_deducts_protocol_fee() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let fee = amount / 100;
+
+            let _ = contract.set_protocol_fee(ProtocolFee {
+                basis_points: 100,
+                minimum_fees: Vec::new(),
+            });
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            let recipient_initial_balance = get_balance(recipient);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            let receipt = contract.get_payment_receipt(0).unwrap();
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                get_balance(recipient),
+                recipient_initial_balance + amount - fee
+            );
+            assert_eq!(contract.get_escrow_balance(schedule_id), amount);
+            assert_eq!(contract.get_collected_fees(None), fee);
+            assert_eq!(receipt.amount, amount - fee);
+            assert_eq!(receipt.fee, fee);
         }
 
         #[ink::test]
-        fn add_token_to_whitelist_works() {
+        fn trigger_payment_deducts_minimum_protocol_fee() {
             let (mut contract, accounts) = init();
 
-            set_sender(accounts.alice);
-            let token_address = AccountId::from([0x9; 32]);
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let minimum_fee = 50000;
 
-            let _ = contract.add_token_to_whitelist(token_address);
+            let _ = contract.set_protocol_fee(ProtocolFee {
+                basis_points: 1,
+                minimum_fees: vec![(None, minimum_fee)],
+            });
 
-            assert_eq!(contract.tokens_whitelist, vec![token_address]);
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(contract.get_schedule_fee(schedule_id), Ok(minimum_fee));
+            assert_eq!(contract.get_collected_fees(None), minimum_fee);
         }
 
         #[ink::test]
-        fn add_token_to_whitelist_by_common_user_fails() {
+        fn protocol_fee_applies_to_existing_schedule_after_accept() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.set_protocol_fee(ProtocolFee {
+                basis_points: 100,
+                minimum_fees: Vec::new(),
+            });
+
+            let fee_before_accept = contract.get_schedule_fee(schedule_id);
+            let accept_result = contract.accept_protocol_fee(schedule_id);
+
+            assert_eq!(fee_before_accept, Ok(0));
+            assert_eq!(accept_result, Ok(()));
+            assert_eq!(contract.get_schedule_fee(schedule_id), Ok(amount / 100));
+        }
+
+        #[ink::test]
+        fn splits_must_fit_in_payment_after_protocol_fee() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let new_schedule_id = Hash::from([0x4; 32]);
+            let amount = 1000;
+            let splits = vec![PaymentSplit {
+                recipient: accounts.charlie,
+                share: SplitShare::Amount(1000),
+            }];
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                accounts.bob,
+                amount,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                splits.clone(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            let _ = contract.set_protocol_fee(ProtocolFee {
+                basis_points: 1000,
+                minimum_fees: Vec::new(),
+            });
+
+            let save_result = contract.save_schedule(
+                new_schedule_id,
+                String::from("task_456"),
+                accounts.bob,
+                amount,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                splits,
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+            let accept_result = contract.accept_protocol_fee(schedule_id);
+
+            set_value_to_transfer(amount);
+            let _ = contract.deposit(schedule_id, amount);
+
+            let charlie_initial_balance = get_balance(accounts.charlie);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let trigger_result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(save_result, Err(TemporaError::WrongScheduleConfiguration));
+            assert_eq!(accept_result, Err(TemporaError::WrongScheduleConfiguration));
+            assert_eq!(trigger_result, Ok(()));
+            assert_eq!(
+                get_balance(accounts.charlie),
+                charlie_initial_balance + amount
+            );
+            assert_eq!(contract.get_collected_fees(None), 0);
+            assert_eq!(contract.get_escrow_balance(schedule_id), 0);
+        }
+
+        #[ink::test]
+        fn accept_protocol_fee_by_other_account_fails() {
             let (mut contract, accounts) = init();
 
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
             set_sender(accounts.bob);
-            let result = contract.add_token_to_whitelist(accounts.bob);
+            let result = contract.accept_protocol_fee(schedule_id);
 
             assert_eq!(result, Err(TemporaError::Unauthorized));
         }
 
         #[ink::test]
-        fn add_token_to_whitelist_twice_fails() {
-            let (mut contract, _) = init();
+        fn withdraw_fees_works() {
+            let (mut contract, accounts) = init();
 
-            let token_address = AccountId::from([0x9; 32]);
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let fee = amount / 100;
 
-            let _ = contract.add_token_to_whitelist(token_address);
-            let result = contract.add_token_to_whitelist(token_address);
+            let _ = contract.set_protocol_fee(ProtocolFee {
+                basis_points: 100,
+                minimum_fees: Vec::new(),
+            });
 
-            assert_eq!(result, Err(TemporaError::TokenIsAlreadyWhiteslited));
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(0);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let treasury_initial_balance = get_balance(accounts.charlie);
+
+            let result = contract.withdraw_fees(None, accounts.charlie, fee);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                get_balance(accounts.charlie),
+                treasury_initial_balance + fee
+            );
+            assert_eq!(contract.get_collected_fees(None), 0);
         }
 
         #[ink::test]
-        fn remove_token_from_whitelist_works() {
-            let (mut contract, _) = init();
+        fn withdraw_fees_fails() {
+            let (mut contract, accounts) = init();
 
-            let token_address = AccountId::from([0x9; 32]);
+            let more_than_collected_result = contract.withdraw_fees(None, accounts.charlie, 1);
 
-            let _ = contract.add_token_to_whitelist(token_address);
-            let _ = contract.remove_token_from_whitelist(token_address);
+            set_sender(accounts.bob);
+            let unauthorized_result = contract.withdraw_fees(None, accounts.bob, 0);
+
+            assert_eq!(
+                more_than_collected_result,
+                Err(TemporaError::InsufficientBalance)
+            );
+            assert_eq!(unauthorized_result, Err(TemporaError::Unauthorized));
+        }
+
+        #[ink::test]
+        fn withdraw_from_escrow_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            let sender_initial_balance = get_balance(accounts.alice);
+
+            let result = contract.withdraw(schedule_id, amount);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(accounts.alice), sender_initial_balance + amount);
+            assert_eq!(contract.get_escrow_balance(schedule_id), amount);
+        }
+
+        #[ink::test]
+        fn withdraw_more_than_escrow_balance_fails() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(amount);
+            let _ = contract.deposit(schedule_id, amount);
+
+            let result = contract.withdraw(schedule_id, 2 * amount);
+
+            assert_eq!(result, Err(TemporaError::InsufficientBalance));
+            assert_eq!(contract.get_escrow_balance(schedule_id), amount);
+        }
+
+        #[ink::test]
+        fn remove_schedule_refunds_escrow() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+            let task_id = String::from("task_123");
+            let recipient = accounts.bob;
+            let amount = 1000000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+
+            let _ = contract.save_schedule(
+                schedule_id,
+                task_id,
+                recipient,
+                amount,
+                token_address,
+                start_time,
+                interval,
+                execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
+
+            set_value_to_transfer(2 * amount);
+            let _ = contract.deposit(schedule_id, 2 * amount);
+
+            let sender_initial_balance = get_balance(accounts.alice);
+
+            let result = contract.remove_schedule(schedule_id);
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                get_balance(accounts.alice),
+                sender_initial_balance + 2 * amount
+            );
+            assert_eq!(contract.get_escrow_balance(schedule_id), 0);
+        }
+
+        #[ink::test]
+        fn save_stream_works() {
+            let (mut contract, accounts) = init();
+
+            let schedule_id = Hash::from([0x3; 32]);
+
+            let result = contract.save_stream(schedule_id, accounts.bob, None, 100, 1000, None);
+
+            let schedule = contract.schedules.get(schedule_id).unwrap();
+            set_sender(accounts.bob);
+            let recipient_schedules = contract.get_user_schedules();
 
-            assert_eq!(contract.tokens_whitelist, Vec::new());
+            assert_eq!(result, Ok(()));
+            assert_eq!(schedule.amount, 100);
+            assert_eq!(
+                schedule.stream,
+                Some(StreamConfiguration {
+                    rate_per_second: 100,
+                    start_time: 1000,
+                    stop_time: None,
+                })
+            );
+            assert_eq!(recipient_schedules.len(), 1);
         }
 
         #[ink::test]
-        fn remove_token_from_whitelist_by_common_user_fails() {
+        fn save_stream_with_stop_before_start_fails() {
             let (mut contract, accounts) = init();
 
-            let token_address = AccountId::from([0x9; 32]);
-
-            let _ = contract.add_token_to_whitelist(token_address);
+            let schedule_id = Hash::from([0x3; 32]);
 
-            set_sender(accounts.bob);
-            let result = contract.remove_token_from_whitelist(token_address);
+            let result =
+                contract.save_stream(schedule_id, accounts.bob, None, 100, 1000, Some(1000));
 
-            assert_eq!(result, Err(TemporaError::Unauthorized));
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
         }
 
         #[ink::test]
-        fn remove_nonexistent_token_from_whitelist_fails() {
-            let (mut contract, _) = init();
+        fn withdraw_stream_pays_accrued_amount() {
+            let (mut contract, accounts) = init();
 
-            let token_address = AccountId::from([0x9; 32]);
+            let schedule_id = Hash::from([0x3; 32]);
+            let recipient = accounts.bob;
+            let token_address = None;
+            let rate_per_second = 100;
+            let start_time = 1000;
+            let stop_time = Some(2000);
 
-            let result = contract.remove_token_from_whitelist(token_address);
+            let _ = contract.save_stream(
+                schedule_id,
+                recipient,
+                token_address,
+                rate_per_second,
+                start_time,
+                stop_time,
+            );
 
-            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
-        }
+            set_value_to_transfer(200000);
+            let _ = contract.deposit(schedule_id, 200000);
 
-        #[ink::test]
-        fn get_whitelisted_tokens_works() {
-            let (mut contract, _) = init();
+            let recipient_initial_balance = get_balance(recipient);
 
-            let token_address = AccountId::from([0x9; 32]);
+            set_timestamp_in_seconds(1500);
+            set_sender(recipient);
+            let first_result = contract.withdraw_stream(schedule_id);
 
-            let _ = contract.add_token_to_whitelist(token_address);
+            set_timestamp_in_seconds(3000);
+            let second_result = contract.withdraw_stream(schedule_id);
 
-            assert_eq!(contract.get_whitelisted_tokens(), vec![token_address]);
+            assert_eq!(first_result, Ok(50000));
+            assert_eq!(second_result, Ok(50000));
+            assert_eq!(get_balance(recipient), recipient_initial_balance + 100000);
+            assert_eq!(contract.get_escrow_balance(schedule_id), 100000);
+            assert_eq!(contract.payment_execution_counts.get(schedule_id), Some(2));
         }
 
         #[ink::test]
-        fn save_fixed_payment_schedule_works() {
+        fn withdraw_stream_by_nonrecipient_fails() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
-            let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
             let token_address = None;
-            let start_time = None;
-            let interval = None;
-            let execution_times = Some(vec![100, 200]);
+            let rate_per_second = 100;
+            let start_time = 1000;
+            let stop_time = Some(2000);
 
-            let _ = contract.save_schedule(
+            let _ = contract.save_stream(
                 schedule_id,
-                task_id,
                 recipient,
-                amount,
                 token_address,
+                rate_per_second,
                 start_time,
-                interval,
-                execution_times,
+                stop_time,
             );
 
-            let owner_schedules = contract.get_user_schedules();
-            set_sender(accounts.bob);
-            let recipient_schedules = contract.get_user_schedules();
-            let schedule_result = contract.schedules.get(schedule_id);
+            set_value_to_transfer(200000);
+            let _ = contract.deposit(schedule_id, 200000);
 
-            assert_eq!(owner_schedules.len(), 1);
-            assert_eq!(owner_schedules[0].schedule_configuration.id, schedule_id);
-            assert_eq!(recipient_schedules.len(), 1);
-            assert_eq!(
-                recipient_schedules[0].schedule_configuration.id,
-                schedule_id
-            );
-            assert!(schedule_result.is_some());
+            set_timestamp_in_seconds(1500);
+            let result = contract.withdraw_stream(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::Unauthorized));
         }
 
         #[ink::test]
-        fn save_recurring_payment_schedule_works() {
+        fn withdraw_accrued_stream_funds_by_sender_fails() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
-            let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
             let token_address = None;
-            let start_time = Some(100);
-            let interval = Some(100);
-            let execution_times = None;
+            let rate_per_second = 100;
+            let start_time = 1000;
+            let stop_time = Some(2000);
 
-            let _ = contract.save_schedule(
+            let _ = contract.save_stream(
                 schedule_id,
-                task_id,
                 recipient,
-                amount,
                 token_address,
+                rate_per_second,
                 start_time,
-                interval,
-                execution_times,
+                stop_time,
             );
 
-            let owner_schedules = contract.get_user_schedules();
-            set_sender(accounts.bob);
-            let recipient_schedules = contract.get_user_schedules();
-            let schedule_result = contract.schedules.get(schedule_id);
+            set_value_to_transfer(200000);
+            let _ = contract.deposit(schedule_id, 200000);
 
-            assert_eq!(owner_schedules.len(), 1);
-            assert_eq!(owner_schedules[0].schedule_configuration.id, schedule_id);
-            assert_eq!(recipient_schedules.len(), 1);
+            set_timestamp_in_seconds(1500);
+            let result = contract.withdraw(schedule_id, 200000);
+            let available_result = contract.withdraw(schedule_id, 150000);
+
+            assert_eq!(result, Err(TemporaError::InsufficientBalance));
+            assert_eq!(available_result, Ok(()));
             assert_eq!(
-                recipient_schedules[0].schedule_configuration.id,
-                schedule_id
+                contract.get_stream_withdrawable_amount(schedule_id),
+                Ok(50000)
             );
-            assert!(schedule_result.is_some());
         }
 
         #[ink::test]
-        fn save_repeated_fixed_payment_fails() {
+        fn remove_stream_settles_accrued_amount() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
-            let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
             let token_address = None;
-            let start_time = None;
-            let interval = None;
-            let execution_times = Some(vec![100, 200]);
+            let rate_per_second = 100;
+            let start_time = 1000;
+            let stop_time = Some(2000);
 
-            let _ = contract.save_schedule(
+            let _ = contract.save_stream(
                 schedule_id,
-                task_id.clone(),
                 recipient,
-                amount,
                 token_address,
+                rate_per_second,
                 start_time,
-                interval,
-                execution_times.clone(),
+                stop_time,
             );
 
-            let result = contract.save_schedule(
-                schedule_id,
-                task_id,
-                recipient,
-                amount,
-                token_address,
-                start_time,
-                interval,
-                execution_times,
-            );
+            set_value_to_transfer(200000);
+            let _ = contract.deposit(schedule_id, 200000);
 
-            assert_eq!(
-                result,
-                Err(TemporaError::ScheduleConfigurationAlreadyExists)
-            );
+            let recipient_initial_balance = get_balance(recipient);
+            let sender_initial_balance = get_balance(accounts.alice);
+
+            set_timestamp_in_seconds(1250);
+            let result = contract.remove_schedule(schedule_id);
+
+            let schedule = contract.schedules.get(schedule_id).unwrap();
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(get_balance(recipient), recipient_initial_balance + 25000);
+            assert_eq!(get_balance(accounts.alice), sender_initial_balance + 175000);
+            assert_eq!(contract.get_escrow_balance(schedule_id), 0);
+            assert_eq!(schedule.stream.unwrap().stop_time, Some(1250));
         }
 
         #[ink::test]
-        fn save_fixed_payment_with_same_caller_and_recipient_fails() {
+        fn trigger_payment_of_stream_fails() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
-            let task_id = String::from("task_123");
-            let recipient = accounts.alice;
-            let amount = 1000000;
+            let recipient = accounts.bob;
             let token_address = None;
-            let start_time = None;
-            let interval = None;
-            let execution_times = Some(vec![100, 200]);
+            let rate_per_second = 100;
+            let start_time = 1000;
+            let stop_time = Some(2000);
 
-            let result = contract.save_schedule(
+            let _ = contract.save_stream(
                 schedule_id,
-                task_id,
                 recipient,
-                amount,
                 token_address,
+                rate_per_second,
                 start_time,
-                interval,
-                execution_times,
+                stop_time,
             );
 
-            assert_eq!(result, Err(TemporaError::CallerCannotBeRecipient));
+            set_value_to_transfer(200000);
+            let _ = contract.deposit(schedule_id, 200000);
+
+            set_timestamp_in_seconds(1500);
+            set_value_to_transfer(0);
+            let result = contract.trigger_payment(schedule_id);
+
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
         }
 
         #[ink::test]
-        fn save_fixed_payment_without_amount_fails() {
+        fn save_split_payment_schedule_works() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
             let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 0;
+            let amount = 10000;
             let token_address = None;
-            let start_time = None;
-            let interval = None;
-            let execution_times = Some(vec![100, 200]);
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let splits = vec![
+                PaymentSplit {
+                    recipient: accounts.charlie,
+                    share: SplitShare::BasisPoints(3333),
+                },
+                PaymentSplit {
+                    recipient: accounts.django,
+                    share: SplitShare::Amount(1000),
+                },
+            ];
 
             let result = contract.save_schedule(
                 schedule_id,
@@ -727,23 +9237,45 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                splits,
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
-            assert_eq!(result, Err(TemporaError::ScheduleAmountCannotBeZero));
+            set_sender(accounts.django);
+            let split_recipient_schedules = contract.get_user_schedules();
+
+            assert_eq!(result, Ok(()));
+            assert_eq!(split_recipient_schedules.len(), 1);
+            assert_eq!(
+                split_recipient_schedules[0].schedule_configuration.id,
+                schedule_id
+            );
         }
 
         #[ink::test]
-        fn save_fixed_payment_with_nonwhitelisted_token_fails() {
+        fn save_split_payment_exceeding_amount_fails() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
             let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
-            let token_address = Some(AccountId::from([0x9; 32]));
-            let start_time = None;
-            let interval = None;
-            let execution_times = Some(vec![100, 200]);
+            let amount = 10000;
+            let token_address = None;
+            let start_time = Some(100);
+            let interval = Some(100);
+            let execution_times = None;
+            let splits = vec![
+                PaymentSplit {
+                    recipient: accounts.charlie,
+                    share: SplitShare::BasisPoints(6000),
+                },
+                PaymentSplit {
+                    recipient: accounts.django,
+                    share: SplitShare::Amount(5000),
+                },
+            ];
 
             let result = contract.save_schedule(
                 schedule_id,
@@ -754,23 +9286,31 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                splits,
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
-            assert_eq!(result, Err(TemporaError::TokenIsNotWhitelisted));
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
         }
 
         #[ink::test]
-        fn save_schedule_without_timestamp_configuration_fails() {
+        fn save_split_payment_to_sender_fails() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
             let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
+            let amount = 10000;
             let token_address = None;
-            let start_time = None;
-            let interval = None;
+            let start_time = Some(100);
+            let interval = Some(100);
             let execution_times = None;
+            let splits = vec![PaymentSplit {
+                recipient: accounts.alice,
+                share: SplitShare::Amount(1000),
+            }];
 
             let result = contract.save_schedule(
                 schedule_id,
@@ -781,25 +9321,39 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                splits,
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
-            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
+            assert_eq!(result, Err(TemporaError::CallerCannotBeRecipient));
         }
 
         #[ink::test]
-        fn remove_schedule_works() {
+        fn save_split_payment_with_repeated_recipient_fails() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
             let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
+            let amount = 10000;
             let token_address = None;
             let start_time = Some(100);
             let interval = Some(100);
             let execution_times = None;
+            let splits = vec![
+                PaymentSplit {
+                    recipient: accounts.charlie,
+                    share: SplitShare::Amount(1000),
+                },
+                PaymentSplit {
+                    recipient: accounts.charlie,
+                    share: SplitShare::Amount(2000),
+                },
+            ];
 
-            let _ = contract.save_schedule(
+            let result = contract.save_schedule(
                 schedule_id,
                 task_id,
                 recipient,
@@ -808,28 +9362,37 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                splits,
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
-            let _ = contract.remove_schedule(schedule_id);
-
-            let schedule_result = contract.schedules.get(schedule_id);
-
-            assert!(schedule_result.is_some());
-            assert!(!schedule_result.unwrap().enabled);
+            assert_eq!(result, Err(TemporaError::WrongScheduleConfiguration));
         }
 
         #[ink::test]
-        fn remove_nonexistent_schedule_fails() {
+        fn trigger_split_payment_works() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
             let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
+            let amount = 10000;
             let token_address = None;
             let start_time = Some(100);
             let interval = Some(100);
             let execution_times = None;
+            let splits = vec![
+                PaymentSplit {
+                    recipient: accounts.charlie,
+                    share: SplitShare::BasisPoints(3333),
+                },
+                PaymentSplit {
+                    recipient: accounts.django,
+                    share: SplitShare::Amount(1000),
+                },
+            ];
 
             let _ = contract.save_schedule(
                 schedule_id,
@@ -840,38 +9403,50 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                splits,
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
-            set_sender(accounts.bob);
-            let result = contract.remove_schedule(schedule_id);
-
-            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
-        }
-
-        #[ink::test]
-        fn remove_schedule_by_nonowner_fails() {
-            let (mut contract, accounts) = init();
+            let recipient_initial_balance = get_balance(recipient);
+            let charlie_initial_balance = get_balance(accounts.charlie);
+            let django_initial_balance = get_balance(accounts.django);
 
-            let schedule_id = Hash::from([0x3; 32]);
+            set_timestamp_in_seconds(100);
+            set_value_to_transfer(amount);
+            let result = contract.trigger_payment(schedule_id);
 
-            set_sender(accounts.bob);
-            let result = contract.remove_schedule(schedule_id);
+            let payment_executions = contract.get_payment_executions(schedule_id, 0, MAX_PAGE_SIZE);
 
-            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
+            assert_eq!(result, Ok(()));
+            // 33.33% of 10000 is 3333, the primary recipient keeps the remainder
+            assert_eq!(get_balance(recipient), recipient_initial_balance + 5667);
+            assert_eq!(
+                get_balance(accounts.charlie),
+                charlie_initial_balance + 3333
+            );
+            assert_eq!(get_balance(accounts.django), django_initial_balance + 1000);
+            assert_eq!(payment_executions.len(), 3);
+            assert_eq!(payment_executions[0].recipient, recipient);
+            assert_eq!(payment_executions[0].amount, 5667);
+            assert_eq!(payment_executions[1].recipient, accounts.charlie);
+            assert_eq!(payment_executions[2].recipient, accounts.django);
         }
 
         #[ink::test]
-        fn update_schedule_works() {
+        fn update_schedule_indexes_new_split_recipients() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
             let task_id = String::from("task_123");
             let recipient = accounts.bob;
-            let amount = 1000000;
+            let amount = 10000;
             let token_address = None;
             let start_time = Some(100);
             let interval = Some(100);
             let execution_times = None;
+            let splits = Vec::new();
 
             let _ = contract.save_schedule(
                 schedule_id,
@@ -882,44 +9457,89 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                splits,
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
             let mut schedule = contract.schedules.get(schedule_id).unwrap();
-            schedule.amount = 2000000;
+            schedule.splits = vec![PaymentSplit {
+                recipient: accounts.eve,
+                share: SplitShare::BasisPoints(5000),
+            }];
 
-            let _ = contract.update_schedule(schedule);
+            let result = contract.update_schedule(schedule);
 
-            let updated_schedule = contract.schedules.get(schedule_id).unwrap();
+            set_sender(accounts.eve);
+            let split_recipient_schedules = contract.get_user_schedules();
 
-            assert_eq!(updated_schedule.amount, 2000000);
+            assert_eq!(result, Ok(()));
+            assert_eq!(split_recipient_schedules.len(), 1);
         }
 
         #[ink::test]
-        fn update_nonexistent_schedule_fails() {
+        fn get_account_schedules_paginates_and_filters_by_role() {
             let (mut contract, accounts) = init();
 
-            let schedule_id = Hash::from([0x3; 32]);
+            for index in 0..3u8 {
+                let _ = contract.save_schedule(
+                    Hash::from([index; 32]),
+                    String::from("task_123"),
+                    accounts.bob,
+                    1000000,
+                    None,
+                    Some(100),
+                    Some(100),
+                    None,
+                    None,
+                    Vec::new(),
+                    EndConditions::default(),
+                    MissedExecutionPolicy::Skip,
+                );
+            }
 
-            let schedule = ScheduleConfiguration {
-                id: schedule_id,
-                task_id: String::from("task_123"),
-                sender: accounts.bob,
-                recipient: accounts.bob,
-                amount: 1000000,
-                token_address: None,
-                start_time: Some(100),
-                interval: Some(100),
-                execution_times: None,
-                enabled: true,
-            };
+            set_sender(accounts.bob);
+            let _ = contract.save_schedule(
+                Hash::from([0x9; 32]),
+                String::from("task_456"),
+                accounts.alice,
+                1000000,
+                None,
+                Some(100),
+                Some(100),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
 
-            let result = contract.update_schedule(schedule);
+            let all_schedules = contract.get_account_schedules(accounts.alice, None, 0, 10);
+            let sent_page =
+                contract.get_account_schedules(accounts.alice, Some(ScheduleRole::Sender), 1, 1);
+            let received_schedules = contract.get_account_schedules(
+                accounts.alice,
+                Some(ScheduleRole::Recipient),
+                0,
+                10,
+            );
 
-            assert_eq!(result, Err(TemporaError::UserScheduleConfigurationNotFound));
+            assert_eq!(all_schedules.len(), 4);
+            assert_eq!(sent_page.len(), 1);
+            assert_eq!(
+                sent_page[0].schedule_configuration.id,
+                Hash::from([0x1; 32])
+            );
+            assert_eq!(received_schedules.len(), 1);
+            assert_eq!(
+                received_schedules[0].schedule_configuration.id,
+                Hash::from([0x9; 32])
+            );
         }
 
         #[ink::test]
-        fn trigger_native_payment_works() {
+        fn get_payment_executions_paginates() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
@@ -927,8 +9547,8 @@ mod tempora_contract {
             let recipient = accounts.bob;
             let amount = 1000000;
             let token_address = None;
-            let start_time = Some(100);
-            let interval = Some(100);
+            let start_time = Some(10000);
+            let interval = Some(3600);
             let execution_times = None;
 
             let _ = contract.save_schedule(
@@ -940,44 +9560,72 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
-            set_value_to_transfer(amount);
-
-            let _ = contract.trigger_payment(recipient, amount, token_address, schedule_id);
+            for slot in 0..3 {
+                set_timestamp_in_seconds(10000 + slot * 3600);
+                set_value_to_transfer(amount);
+                let _ = contract.trigger_payment(schedule_id);
+            }
 
-            let user_schedules = contract.get_user_schedules();
-            let payment_executions = contract.payment_executions.get(schedule_id).unwrap();
+            let first_page = contract.get_payment_executions(schedule_id, 0, 2);
+            let second_page = contract.get_payment_executions(schedule_id, 2, 2);
 
-            assert_eq!(user_schedules[0].payment_executions.len(), 1);
-            assert_eq!(payment_executions.len(), 1);
+            assert_eq!(first_page.len(), 2);
+            assert_eq!(first_page[0].timestamp, 10000);
+            assert_eq!(second_page.len(), 1);
+            assert_eq!(second_page[0].timestamp, 10000 + 2 * 3600);
         }
 
         #[ink::test]
-        fn trigger_payment_with_same_caller_recipient_fails() {
+        fn get_user_schedules_returns_latest_payment_executions() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
+            let amount = 1000;
+            let payments = u64::from(MAX_PAGE_SIZE) + 10;
 
-            set_sender(accounts.bob);
-            let result = contract.trigger_payment(accounts.bob, 1000000, None, schedule_id);
-
-            assert_eq!(result, Err(TemporaError::CallerCannotBeRecipient));
-        }
-
-        #[ink::test]
-        fn trigger_payment_without_amount_fails() {
-            let (mut contract, accounts) = init();
+            let _ = contract.save_schedule(
+                schedule_id,
+                String::from("task_123"),
+                accounts.bob,
+                amount,
+                None,
+                Some(10000),
+                Some(3600),
+                None,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
+            );
 
-            let schedule_id = Hash::from([0x3; 32]);
+            for slot in 0..payments {
+                set_timestamp_in_seconds(10000 + slot * 3600);
+                set_value_to_transfer(amount);
+                let _ = contract.trigger_payment(schedule_id);
+            }
 
-            let result = contract.trigger_payment(accounts.bob, 0, None, schedule_id);
+            let payment_executions = &contract.get_user_schedules()[0].payment_executions;
+            let last_page =
+                contract.get_payment_executions(schedule_id, MAX_PAGE_SIZE, MAX_PAGE_SIZE);
 
-            assert_eq!(result, Err(TemporaError::ScheduleAmountCannotBeZero));
+            assert_eq!(
+                contract.payment_execution_counts.get(schedule_id),
+                Some(payments as u32)
+            );
+            assert_eq!(payment_executions.len(), MAX_PAGE_SIZE as usize);
+            assert_eq!(payment_executions[0].timestamp, 10000 + 10 * 3600);
+            assert_eq!(last_page.len(), 10);
+            assert_eq!(last_page[9].timestamp, 10000 + (payments - 1) * 3600);
         }
 
         #[ink::test]
-        fn trigger_native_payment_without_sufficient_balance_fails() {
+        fn get_payment_receipt_works() {
             let (mut contract, accounts) = init();
 
             let schedule_id = Hash::from([0x3; 32]);
@@ -985,8 +9633,8 @@ mod tempora_contract {
             let recipient = accounts.bob;
             let amount = 1000000;
             let token_address = None;
-            let start_time = Some(100);
-            let interval = Some(100);
+            let start_time = Some(10000);
+            let interval = Some(3600);
             let execution_times = None;
 
             let _ = contract.save_schedule(
@@ -998,13 +9646,44 @@ mod tempora_contract {
                 start_time,
                 interval,
                 execution_times,
+                None,
+                Vec::new(),
+                EndConditions::default(),
+                MissedExecutionPolicy::Skip,
             );
 
-            set_value_to_transfer(amount / 2);
+            set_timestamp_in_seconds(10000);
+            set_value_to_transfer(amount);
+            let _ = contract.trigger_payment(schedule_id);
+
+            set_timestamp_in_seconds(10000 + 3600 + 1);
+            set_value_to_transfer(amount);
+            let _ = contract.trigger_payment(schedule_id);
+
+            let receipt = contract.get_payment_receipt(1).unwrap();
+
+            assert_eq!(receipt.receipt_id, 1);
+            assert_eq!(receipt.schedule_id, schedule_id);
+            assert_eq!(receipt.slot, 10000 + 3600);
+            assert_eq!(receipt.slot_index, Some(1));
+            assert_eq!(receipt.timestamp, 10000 + 3600 + 1);
+            assert_eq!(receipt.executor, accounts.alice);
+            assert_eq!(receipt.recipient, recipient);
+            assert_eq!(receipt.amount, amount);
+            assert_eq!(receipt.token_address, None);
+            assert_eq!(receipt.outcome, PaymentOutcome::Success);
+            assert_eq!(
+                contract.get_user_schedules()[0].payment_executions[1],
+                receipt
+            );
+            assert_eq!(contract.get_payment_receipt(2), None);
 
-            let result = contract.trigger_payment(recipient, amount, token_address, schedule_id);
+            let emitted_events = get_emitted_events();
 
-            assert_eq!(result, Err(TemporaError::InsufficientBalance));
+            assert!(emitted_events.iter().any(|event| matches!(
+                event,
+                Event::PaymentExecuted(event) if event.receipt_id == 1
+            )));
         }
     }
 }
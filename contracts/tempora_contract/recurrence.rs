@@ -0,0 +1,554 @@
+//! Calendar-aware recurrence rules: a subset of the iCalendar RRULE (RFC 5545) covering
+//! `FREQ`, `INTERVAL`, `BYMONTHDAY`, `BYDAY` and `COUNT`.
+//!
+//! Occurrences are evaluated in UTC on timestamps in seconds. The rule's start (`DTSTART`)
+//! sets the time of day of every occurrence and the first period. Weeks start on Monday.
+//!
+//! Daily rules take no `BYMONTHDAY` or `BYDAY` filter and weekly rules no `BYMONTHDAY`, so
+//! only monthly rules can go several periods without an occurrence.
+
+use ink::prelude::vec::Vec;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Days between 0000-03-01 and 1970-01-01 in the proleptic Gregorian calendar.
+const UNIX_EPOCH_DAY: u64 = 719_468;
+
+const DAYS_PER_ERA: u64 = 146_097;
+
+/// Maximum number of entries of `by_month_day` or `by_day`.
+pub const MAX_RULE_ENTRIES: usize = 31;
+
+/// Maximum `count` of a rule, which bounds the work of evaluating it.
+pub const MAX_RECURRENCE_COUNT: u32 = 1_000;
+
+/// Consecutive periods without occurrences after which a rule is considered exhausted.
+///
+/// Weekdays fall on the same days of the month every 28 years (between 1901 and 2099), so a
+/// monthly rule with no occurrence in that many periods has none left.
+const MAX_EMPTY_PERIODS: u32 = 28 * 12;
+
+#[derive(Debug, scale::Decode, scale::Encode, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, scale::Decode, scale::Encode, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    fn of_day(day: u64) -> Self {
+        // 1970-01-01 was a Thursday
+        match (day + 3) % 7 {
+            0 => Weekday::Monday,
+            1 => Weekday::Tuesday,
+            2 => Weekday::Wednesday,
+            3 => Weekday::Thursday,
+            4 => Weekday::Friday,
+            5 => Weekday::Saturday,
+            _ => Weekday::Sunday,
+        }
+    }
+
+    fn days_from_monday(&self) -> u64 {
+        *self as u64
+    }
+}
+
+/// A `BYDAY` entry. `nth` selects the nth such weekday of the month, counting from the end
+/// when negative (`2FR`, `-1MO`), and is only allowed with a monthly frequency.
+#[derive(Debug, scale::Decode, scale::Encode, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct WeekdayRule {
+    pub weekday: Weekday,
+    pub nth: Option<i8>,
+}
+
+#[derive(Debug, scale::Decode, scale::Encode, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    /// Number of periods between two active periods, at least 1
+    pub interval: u32,
+    /// Days of the month, counting from the end when negative
+    pub by_month_day: Vec<i8>,
+    pub by_day: Vec<WeekdayRule>,
+    /// Total number of occurrences
+    pub count: Option<u32>,
+}
+
+impl RecurrenceRule {
+    pub fn is_valid(&self) -> bool {
+        let valid_month_days = self
+            .by_month_day
+            .iter()
+            .all(|month_day| *month_day != 0 && (-31..=31).contains(month_day));
+
+        let valid_weekdays = self.by_day.iter().all(|rule| match rule.nth {
+            None => true,
+            Some(nth) => {
+                self.frequency == Frequency::Monthly && nth != 0 && (-5..=5).contains(&nth)
+            }
+        });
+
+        let valid_filters = match self.frequency {
+            Frequency::Daily => self.by_month_day.is_empty() && self.by_day.is_empty(),
+            Frequency::Weekly => self.by_month_day.is_empty(),
+            Frequency::Monthly => true,
+        };
+
+        self.interval > 0
+            && valid_filters
+            && self.by_month_day.len() <= MAX_RULE_ENTRIES
+            && self.by_day.len() <= MAX_RULE_ENTRIES
+            && !matches!(self.count, Some(count) if count == 0 || count > MAX_RECURRENCE_COUNT)
+            && valid_month_days
+            && valid_weekdays
+    }
+
+    /// Returns the occurrences of the rule started at `start` that are not before `from`,
+    /// in ascending order.
+    pub fn occurrences_from(&self, start: u64, from: u64) -> Occurrences<'_> {
+        let mut occurrences = Occurrences {
+            rule: self,
+            start,
+            from,
+            period: 0,
+            pending: Vec::new(),
+            emitted: 0,
+        };
+
+        // Without a count, earlier periods can be skipped as they are never emitted
+        if self.count.is_none() && from > start {
+            occurrences.period = self.period_of(start, from);
+        }
+
+        occurrences
+    }
+
+    fn period_of(&self, start: u64, timestamp: u64) -> u64 {
+        let start_day = start / SECONDS_PER_DAY;
+        let day = timestamp / SECONDS_PER_DAY;
+        let interval = u64::from(self.interval);
+
+        match self.frequency {
+            Frequency::Daily => (day - start_day) / interval,
+            Frequency::Weekly => (day - week_start(start_day)) / (7 * interval),
+            Frequency::Monthly => (month_index(day) - month_index(start_day)) / interval,
+        }
+    }
+
+    /// Returns the sorted occurrence days of the given period.
+    fn period_days(&self, start_day: u64, period: u64) -> Vec<u64> {
+        let interval = u64::from(self.interval);
+
+        let mut days = match self.frequency {
+            Frequency::Daily => ink::prelude::vec![start_day + period * interval],
+            Frequency::Weekly => {
+                let first_day = week_start(start_day) + period * 7 * interval;
+
+                if self.by_day.is_empty() {
+                    ink::prelude::vec![first_day + Weekday::of_day(start_day).days_from_monday()]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|rule| first_day + rule.weekday.days_from_monday())
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let month = month_index(start_day) + period * interval;
+                let first_day = days_from_civil(month / 12, month % 12 + 1, 1);
+                let month_length = days_in_month(month / 12, month % 12 + 1);
+
+                if !self.by_month_day.is_empty() {
+                    self.by_month_day
+                        .iter()
+                        .filter_map(|month_day| resolve_month_day(*month_day, month_length))
+                        .map(|month_day| first_day + month_day - 1)
+                        .filter(|day| self.matches_weekday(*day))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    (first_day..first_day + month_length)
+                        .filter(|day| self.matches_weekday(*day))
+                        .collect()
+                } else {
+                    let (_, _, start_month_day) = civil_from_days(start_day);
+
+                    resolve_month_day(start_month_day as i8, month_length)
+                        .map(|month_day| ink::prelude::vec![first_day + month_day - 1])
+                        .unwrap_or_default()
+                }
+            }
+        };
+
+        days.sort_unstable();
+        days.dedup();
+
+        days
+    }
+
+    fn matches_weekday(&self, day: u64) -> bool {
+        if self.by_day.is_empty() {
+            return true;
+        }
+
+        let weekday = Weekday::of_day(day);
+        let (year, month, month_day) = civil_from_days(day);
+        let month_length = days_in_month(year, month);
+
+        self.by_day.iter().any(|rule| {
+            rule.weekday == weekday
+                && match rule.nth {
+                    None => true,
+                    Some(nth) if nth > 0 => (month_day - 1) / 7 + 1 == nth as u64,
+                    Some(nth) => (month_length - month_day) / 7 + 1 == nth.unsigned_abs() as u64,
+                }
+        })
+    }
+}
+
+pub struct Occurrences<'a> {
+    rule: &'a RecurrenceRule,
+    start: u64,
+    from: u64,
+    period: u64,
+    /// Occurrences of the current period still to be returned, in descending order
+    pending: Vec<u64>,
+    emitted: u32,
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let start_day = self.start / SECONDS_PER_DAY;
+        let time_of_day = self.start % SECONDS_PER_DAY;
+
+        loop {
+            if matches!(self.rule.count, Some(count) if self.emitted >= count) {
+                return None;
+            }
+
+            if let Some(occurrence) = self.pending.pop() {
+                self.emitted += 1;
+
+                if occurrence >= self.from {
+                    return Some(occurrence);
+                }
+
+                continue;
+            }
+
+            let mut empty_periods = 0;
+
+            while self.pending.is_empty() {
+                if empty_periods == MAX_EMPTY_PERIODS {
+                    return None;
+                }
+
+                self.pending = self
+                    .rule
+                    .period_days(start_day, self.period)
+                    .into_iter()
+                    .filter_map(|day| day.checked_mul(SECONDS_PER_DAY)?.checked_add(time_of_day))
+                    .filter(|occurrence| *occurrence >= self.start)
+                    .rev()
+                    .collect();
+
+                self.period += 1;
+                empty_periods += 1;
+            }
+        }
+    }
+}
+
+fn resolve_month_day(month_day: i8, month_length: u64) -> Option<u64> {
+    let resolved = if month_day > 0 {
+        month_day as u64
+    } else {
+        (month_length + 1).checked_sub(month_day.unsigned_abs() as u64)?
+    };
+
+    (1..=month_length).contains(&resolved).then_some(resolved)
+}
+
+fn week_start(day: u64) -> u64 {
+    day - Weekday::of_day(day).days_from_monday()
+}
+
+/// Months since year 0, with January as 0.
+fn month_index(day: u64) -> u64 {
+    let (year, month, _) = civil_from_days(day);
+
+    year * 12 + month - 1
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+
+    days_from_civil(next_year, next_month, 1) - days_from_civil(year, month, 1)
+}
+
+/// Converts days since the Unix epoch into a (year, month, day) date.
+fn civil_from_days(day: u64) -> (u64, u64, u64) {
+    let days = day + UNIX_EPOCH_DAY;
+    let era = days / DAYS_PER_ERA;
+    let day_of_era = days % DAYS_PER_ERA;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month_day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, month_day)
+}
+
+/// Converts a (year, month, day) date into days since the Unix epoch.
+fn days_from_civil(year: u64, month: u64, month_day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let shifted_month = (month + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + month_day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * DAYS_PER_ERA + day_of_era - UNIX_EPOCH_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-01 09:00:00 UTC, a Monday
+    const START: u64 = 1_704_099_600;
+
+    fn rule(frequency: Frequency) -> RecurrenceRule {
+        RecurrenceRule {
+            frequency,
+            interval: 1,
+            by_month_day: Vec::new(),
+            by_day: Vec::new(),
+            count: None,
+        }
+    }
+
+    fn timestamp(year: u64, month: u64, month_day: u64) -> u64 {
+        days_from_civil(year, month, month_day) * SECONDS_PER_DAY + 9 * 3600
+    }
+
+    #[test]
+    fn civil_date_conversion_round_trips() {
+        for day in [0, 59, 10_957, 11_016, 19_723, 19_782, 47_541] {
+            let (year, month, month_day) = civil_from_days(day);
+
+            assert_eq!(days_from_civil(year, month, month_day), day);
+        }
+
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn monthly_rule_keeps_day_of_month() {
+        let occurrences: Vec<u64> = rule(Frequency::Monthly)
+            .occurrences_from(START, START)
+            .take(3)
+            .collect();
+
+        assert_eq!(
+            occurrences,
+            vec![
+                timestamp(2024, 1, 1),
+                timestamp(2024, 2, 1),
+                timestamp(2024, 3, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_rule_with_last_month_day_works() {
+        let mut last_day_rule = rule(Frequency::Monthly);
+        last_day_rule.by_month_day = vec![-1];
+
+        let occurrences: Vec<u64> = last_day_rule
+            .occurrences_from(START, START)
+            .take(3)
+            .collect();
+
+        assert_eq!(
+            occurrences,
+            vec![
+                timestamp(2024, 1, 31),
+                timestamp(2024, 2, 29),
+                timestamp(2024, 3, 31)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_rule_skips_months_without_the_day() {
+        let mut day_31_rule = rule(Frequency::Monthly);
+        day_31_rule.by_month_day = vec![31];
+
+        let occurrences: Vec<u64> = day_31_rule.occurrences_from(START, START).take(3).collect();
+
+        assert_eq!(
+            occurrences,
+            vec![
+                timestamp(2024, 1, 31),
+                timestamp(2024, 3, 31),
+                timestamp(2024, 5, 31)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_rule_with_nth_weekday_works() {
+        let mut second_friday_rule = rule(Frequency::Monthly);
+        second_friday_rule.by_day = vec![WeekdayRule {
+            weekday: Weekday::Friday,
+            nth: Some(2),
+        }];
+
+        let occurrences: Vec<u64> = second_friday_rule
+            .occurrences_from(START, START)
+            .take(2)
+            .collect();
+
+        assert_eq!(
+            occurrences,
+            vec![timestamp(2024, 1, 12), timestamp(2024, 2, 9)]
+        );
+    }
+
+    #[test]
+    fn sparse_monthly_rule_finds_next_occurrence() {
+        let mut friday_13_rule = rule(Frequency::Monthly);
+        friday_13_rule.by_month_day = vec![13];
+        friday_13_rule.by_day = vec![WeekdayRule {
+            weekday: Weekday::Friday,
+            nth: None,
+        }];
+
+        let occurrences: Vec<u64> = friday_13_rule
+            .occurrences_from(START, START)
+            .take(2)
+            .collect();
+
+        assert_eq!(
+            occurrences,
+            vec![timestamp(2024, 9, 13), timestamp(2024, 12, 13)]
+        );
+        assert!(!RecurrenceRule {
+            frequency: Frequency::Daily,
+            ..friday_13_rule.clone()
+        }
+        .is_valid());
+        assert!(!RecurrenceRule {
+            frequency: Frequency::Weekly,
+            by_day: Vec::new(),
+            ..friday_13_rule
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn weekly_rule_with_interval_works() {
+        let mut every_other_friday_rule = rule(Frequency::Weekly);
+        every_other_friday_rule.interval = 2;
+        every_other_friday_rule.by_day = vec![WeekdayRule {
+            weekday: Weekday::Friday,
+            nth: None,
+        }];
+
+        let occurrences: Vec<u64> = every_other_friday_rule
+            .occurrences_from(START, START)
+            .take(3)
+            .collect();
+
+        assert_eq!(
+            occurrences,
+            vec![
+                timestamp(2024, 1, 5),
+                timestamp(2024, 1, 19),
+                timestamp(2024, 2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn rule_with_count_stops_after_count() {
+        let mut daily_rule = rule(Frequency::Daily);
+        daily_rule.count = Some(3);
+
+        let occurrences: Vec<u64> = daily_rule
+            .occurrences_from(START, timestamp(2024, 1, 2))
+            .collect();
+
+        assert_eq!(
+            occurrences,
+            vec![timestamp(2024, 1, 2), timestamp(2024, 1, 3)]
+        );
+    }
+
+    #[test]
+    fn occurrences_from_skips_earlier_periods() {
+        let occurrences: Vec<u64> = rule(Frequency::Monthly)
+            .occurrences_from(START, timestamp(2025, 6, 15))
+            .take(1)
+            .collect();
+
+        assert_eq!(occurrences, vec![timestamp(2025, 7, 1)]);
+    }
+
+    #[test]
+    fn rule_with_nth_weekday_on_weekly_frequency_is_invalid() {
+        let mut weekly_rule = rule(Frequency::Weekly);
+        weekly_rule.by_day = vec![WeekdayRule {
+            weekday: Weekday::Friday,
+            nth: Some(1),
+        }];
+
+        assert!(!weekly_rule.is_valid());
+        assert!(!RecurrenceRule {
+            interval: 0,
+            ..rule(Frequency::Daily)
+        }
+        .is_valid());
+    }
+}
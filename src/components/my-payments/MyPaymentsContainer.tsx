@@ -6,7 +6,10 @@ import {
   TEMPORA_CONTRACT_MESSAGES,
 } from '@/lib/hooks/useContract';
 import useWallet from '@/lib/hooks/useWallet';
-import { ScheduleConfiguration } from '@/lib/models/schedule-configuration.model';
+import {
+  PaymentReceipt,
+  ScheduleConfiguration,
+} from '@/lib/models/schedule-configuration.model';
 import chainsConfigState from '@/lib/state/chainsConfig.atom';
 import contractMetadata from '@lib/contracts/tempora_contract_metadata.json';
 import { AlertCircle } from 'lucide-react';
@@ -19,7 +22,7 @@ import PaymentCard from './PaymentCard';
 interface ScheduleConfigurationsResult {
   Ok?: {
     scheduleConfiguration: ScheduleConfiguration;
-    paymentExecutions: number[];
+    paymentExecutions: PaymentReceipt[];
   }[];
   Err?: {};
 }
@@ -77,12 +80,11 @@ const MyPaymentsContainer: FC<{}> = () => {
               <p className="text-2xl font-bold text-center">Incoming</p>
               <div className="flex flex-1 gap-4 flex-col mt-4 h-[675px] overflow-auto">
                 {incomingPayments?.map(
-                  ({ scheduleConfiguration, paymentExecutions }, index) => {
+                  ({ scheduleConfiguration }, index) => {
                     return (
                       <PaymentCard
                         key={index}
                         scheduleConfiguration={scheduleConfiguration}
-                        paymentExecutions={paymentExecutions}
                         address={scheduleConfiguration.sender}
                       />
                     );
@@ -98,12 +100,11 @@ const MyPaymentsContainer: FC<{}> = () => {
               <p className="text-2xl font-bold text-center">Outgoing</p>
               <div className="flex-1 flex gap-4 flex-col mt-4 h-[675px] overflow-auto">
                 {outgoingPayments?.map(
-                  ({ scheduleConfiguration, paymentExecutions }, index) => {
+                  ({ scheduleConfiguration }, index) => {
                     return (
                       <PaymentCard
                         key={index}
                         scheduleConfiguration={scheduleConfiguration}
-                        paymentExecutions={paymentExecutions}
                         address={scheduleConfiguration.recipient}
                         showActions={true}
                       />
@@ -41,7 +41,10 @@ import {
   getTokenSymbol,
 } from '@/lib/helpers/polkadotjs.helper';
 import useSchedulePayment from '@/lib/hooks/useSchedulePayment';
-import { ScheduleConfiguration } from '@/lib/models/schedule-configuration.model';
+import {
+  ScheduleConfiguration,
+  ScheduleStatus,
+} from '@/lib/models/schedule-configuration.model';
 import chainsConfigState from '@/lib/state/chainsConfig.atom';
 import { formatAddress } from '@/lib/utils/address';
 import {
@@ -62,16 +65,15 @@ import * as z from 'zod';
 
 const PaymentCard: FC<{
   scheduleConfiguration: ScheduleConfiguration;
-  paymentExecutions: number[];
   address: string;
   showActions?: boolean;
-}> = ({ scheduleConfiguration, paymentExecutions, address, showActions }) => {
+}> = ({ scheduleConfiguration, address, showActions }) => {
   const { originConfig } = useRecoilValue(chainsConfigState);
   let {
     id: scheduleHash,
     taskId,
     amount,
-    enabled,
+    status,
     interval,
     executionTimes,
     startTime,
@@ -149,8 +151,9 @@ const PaymentCard: FC<{
     },
   });
 
-  const hasPaymentFinised =
-    !interval && executionTimes?.length === paymentExecutions?.length;
+  const isActive = status === ScheduleStatus.Active;
+
+  const hasPaymentFinised = status === ScheduleStatus.Completed;
 
   return (
     <Card className="w-[350px]">
@@ -158,7 +161,7 @@ const PaymentCard: FC<{
         <CardTitle className="flex justify-between">
           <p className="flex items-center gap-3">
             {amountFormatted}
-            {showActions && enabled && !hasPaymentFinised && (
+            {showActions && isActive && (
               <>
                 {isUpdatingScheduledPayment ? (
                   <Skeleton className="w-[0.75em] h-[0.75em]" />
@@ -189,12 +192,10 @@ const PaymentCard: FC<{
               </>
             )}
           </p>
-          {enabled ? (
-            hasPaymentFinised ? (
-              <Check className="text-green-600" />
-            ) : (
-              <Hourglass />
-            )
+          {hasPaymentFinised ? (
+            <Check className="text-green-600" />
+          ) : isActive || status === ScheduleStatus.Paused ? (
+            <Hourglass />
           ) : (
             <X className="text-red-600" />
           )}
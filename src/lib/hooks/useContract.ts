@@ -11,7 +11,13 @@ import {
   getExecuteContractExtrinsic,
   queryContract,
 } from '../helpers/polkadotjs.contracts.helper';
-import { ScheduleConfiguration } from '../models/schedule-configuration.model';
+import {
+  EndConditions,
+  MissedExecutionPolicy,
+  PaymentSplit,
+  RecurrenceRule,
+  ScheduleConfiguration,
+} from '../models/schedule-configuration.model';
 import { ChainConfiguration } from './useChainsConfig';
 
 export const TEMPORA_CONTRACT_ADDRESS: string =
@@ -138,6 +144,10 @@ const useContract = (originConfig: ChainConfiguration) => {
    * @param startTime - The start time of the schedule (optional)
    * @param interval - The interval of the schedule (optional)
    * @param executionTimes - The execution times of the schedule (optional)
+   * @param recurrence - A calendar recurrence rule replacing the interval (optional)
+   * @param splits - Additional recipients of every payment (optional)
+   * @param endConditions - Limits after which the schedule is completed (optional)
+   * @param missedExecutionPolicy - What happens to slots not paid in time (optional)
    *
    * @returns An extrinsic to save a schedule based on its configuration.
    */
@@ -150,7 +160,11 @@ const useContract = (originConfig: ChainConfiguration) => {
     tokenAddress?: string,
     startTime?: BN,
     interval?: BN,
-    executionTimes?: BN[]
+    executionTimes?: BN[],
+    recurrence?: RecurrenceRule,
+    splits: PaymentSplit[] = [],
+    endConditions: EndConditions = {},
+    missedExecutionPolicy: MissedExecutionPolicy = MissedExecutionPolicy.Skip
   ) => {
     const params = [
       scheduleHash,
@@ -161,6 +175,10 @@ const useContract = (originConfig: ChainConfiguration) => {
       startTime,
       interval,
       executionTimes,
+      recurrence,
+      splits,
+      endConditions,
+      missedExecutionPolicy,
     ];
 
     return getMessageExtrinsic(
@@ -174,31 +192,30 @@ const useContract = (originConfig: ChainConfiguration) => {
   };
 
   /**
-   * Constructs an extrinsic to trigger a payment.
+   * Constructs an extrinsic to trigger a payment. The contract pays the stored recipient and
+   * amount of the schedule; native payments are funded with the transferred amount, while
+   * PSP22 payments are drawn from the sender's allowance and transfer no value.
    *
    * @param sender - The sender's address initiating the payment.
-   * @param recipient - The recipient's address receiving the payment.
    * @param amount - The amount of the payment.
+   * @param tokenAddress - The PSP22 token of the payment, undefined for native payments.
    * @param scheduleHash - The hash of the payment schedule.
    *
    * @returns A promise that resolves with the extrinsic to trigger the payment.
    */
   const getTriggerPaymentExtrinsic = async (
     sender: string,
-    recipient: string,
     amount: BN,
     tokenAddress: string | undefined,
     scheduleHash: `0x${string}`
   ) => {
-    const params = [recipient, amount, tokenAddress, scheduleHash];
-
     return getMessageExtrinsic(
       sender,
       TEMPORA_CONTRACT_ADDRESS,
       temporaContractMetadata,
       TEMPORA_CONTRACT_MESSAGES.TRIGGER_PAYMENT,
-      amount,
-      params
+      tokenAddress ? undefined : amount,
+      [scheduleHash]
     );
   };
 
@@ -205,7 +205,6 @@ const useSchedulePayment = () => {
 
     const triggerPaymentTx = await getTriggerPaymentExtrinsic(
       account!.address,
-      recipient,
       amountByTxBN,
       paymentTokenAddress,
       schedulePaymentHash
@@ -8,6 +8,63 @@ import {
   SchedulePaymentType,
 } from './schedule-payment.model';
 
+/**
+ * Lifecycle status of a schedule.
+ */
+export enum ScheduleStatus {
+  Active = 'Active',
+  Paused = 'Paused',
+  Cancelled = 'Cancelled',
+  Completed = 'Completed',
+  Expired = 'Expired',
+}
+
+/**
+ * What happens to the slots of a schedule that were not paid in time.
+ */
+export enum MissedExecutionPolicy {
+  Skip = 'Skip',
+  CatchUp = 'CatchUp',
+  Accumulate = 'Accumulate',
+}
+
+/**
+ * Calendar-aware recurrence rule, a subset of the iCalendar RRULE.
+ */
+export interface RecurrenceRule {
+  frequency: 'Daily' | 'Weekly' | 'Monthly';
+  interval: number;
+  byMonthDay: number[];
+  byDay: { weekday: string; nth?: number }[];
+  count?: number;
+}
+
+/**
+ * Continuous payment of a rate per second.
+ */
+export interface StreamConfiguration {
+  ratePerSecond: string;
+  startTime: number;
+  stopTime?: number;
+}
+
+/**
+ * Part of every payment of a schedule that goes to an additional recipient.
+ */
+export interface PaymentSplit {
+  recipient: string;
+  share: { Amount: string } | { BasisPoints: number };
+}
+
+/**
+ * Optional limits of a schedule. It is completed as soon as any of them is reached.
+ */
+export interface EndConditions {
+  endTime?: number;
+  maxExecutions?: number;
+  maxTotalAmount?: string;
+}
+
 /**
  * Represents the configuration of a scheduled payment task.
  */
@@ -21,7 +78,30 @@ export interface ScheduleConfiguration {
   startTime?: number;
   interval?: number;
   executionTimes?: number[];
-  enabled: boolean;
+  recurrence?: RecurrenceRule;
+  stream?: StreamConfiguration;
+  splits: PaymentSplit[];
+  endConditions: EndConditions;
+  missedExecutionPolicy: MissedExecutionPolicy;
+  status: ScheduleStatus;
+}
+
+/**
+ * Record of a single payment attempt to one recipient.
+ */
+export interface PaymentReceipt {
+  receiptId: number;
+  scheduleId: `0x${string}`;
+  slot: number;
+  slotIndex?: number;
+  timestamp: number;
+  blockNumber: number;
+  executor: string;
+  recipient: string;
+  amount: string;
+  fee: string;
+  tokenAddress?: string;
+  outcome: 'Success' | { Failed: string };
 }
 
 export const getOakConfigurationFromSchedule = (